@@ -0,0 +1,36 @@
+/* Copyright 2021 Hwakyeom Kim(=just-do-halee) */
+
+/* C interface of collectfiles, built with `--features ffi`. */
+
+#ifndef COLLECTFILES_H
+#define COLLECTFILES_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct CollectFilesConfig CollectFilesConfig;
+
+/* NULL if root is NULL or not UTF-8. */
+CollectFilesConfig *collectfiles_new(const char *root);
+void collectfiles_free(CollectFilesConfig *config);
+
+/* setters return 0 on success, -1 on a NULL or invalid argument. */
+int collectfiles_set_max_depth(CollectFilesConfig *config, size_t n);
+int collectfiles_set_target_regex(CollectFilesConfig *config, const char *regex);
+int collectfiles_set_skip_hidden(CollectFilesConfig *config, int yes);
+int collectfiles_set_ignore_files(CollectFilesConfig *config, int yes);
+int collectfiles_add_prune_dir(CollectFilesConfig *config, const char *name);
+
+/* *len paths, unreadable directories skipped; NULL when the walk panicked.
+   Release with collectfiles_free_paths. */
+char **collectfiles_collect(const CollectFilesConfig *config, size_t *len);
+void collectfiles_free_paths(char **paths, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* COLLECTFILES_H */
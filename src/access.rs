@@ -0,0 +1,101 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{CollectError, Operation};
+
+/// Result of `check_access`, the readability of the root and, if asked, its entries.
+#[derive(Debug)]
+pub struct AccessReport {
+    pub root: PathBuf,
+    /// whether the root itself could be listed.
+    pub root_readable: bool,
+    /// entries of the root probed, 0 when children were not checked.
+    pub checked: usize,
+    /// every probe that failed, the root first.
+    pub errors: Vec<CollectError>,
+}
+
+impl AccessReport {
+    /// nothing failed.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for AccessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.root_readable {
+            return match self.errors.first() {
+                Some(e) => write!(f, "cannot read {}: {}", self.root.display(), e.source),
+                None => write!(f, "cannot read {}", self.root.display()),
+            };
+        }
+        write!(
+            f,
+            "{} readable, {} of {} entries denied",
+            self.root.display(),
+            self.errors.len(),
+            self.checked
+        )?;
+        for e in self.errors.iter() {
+            write!(f, "\n  {}", e)?;
+        }
+        Ok(())
+    }
+}
+
+/// lists `root` and, with `children`, lists its directories and opens its regular files.
+/// FIFOs, sockets and devices are never opened, a FIFO without a writer would block.
+pub(crate) fn check(root: &Path, children: bool) -> AccessReport {
+    let entries = match fs::read_dir(root) {
+        Ok(read) => read,
+        Err(e) => {
+            return AccessReport {
+                root: root.to_path_buf(),
+                root_readable: false,
+                checked: 0,
+                errors: vec![CollectError::new(root.to_path_buf(), Operation::ReadDir, e)],
+            }
+        }
+    };
+    if !children {
+        return AccessReport {
+            root: root.to_path_buf(),
+            root_readable: true,
+            checked: 0,
+            errors: Vec::new(),
+        };
+    }
+    let paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    let errors = paths
+        .par_iter()
+        .filter_map(|path| {
+            let probe = match fs::metadata(path) {
+                Ok(meta) if meta.is_dir() => fs::read_dir(path)
+                    .map(drop)
+                    .map_err(|e| (Operation::ReadDir, e)),
+                Ok(meta) if meta.is_file() => fs::File::open(path)
+                    .map(drop)
+                    .map_err(|e| (Operation::Content, e)),
+                Ok(_) => Ok(()),
+                Err(e) => Err((Operation::Metadata, e)),
+            };
+            probe
+                .err()
+                .map(|(op, e)| CollectError::new(path.clone(), op, e))
+        })
+        .collect();
+    AccessReport {
+        root: root.to_path_buf(),
+        root_readable: true,
+        checked: paths.len(),
+        errors,
+    }
+}
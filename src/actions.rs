@@ -0,0 +1,741 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    hook::{self, Hook},
+    locked::{is_locked, Settle},
+    template, CollectError, Operation, WalkConfig,
+};
+
+/// What to do when the destination of an action already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collision {
+    Skip,
+    Overwrite,
+    /// picks the first free `name (n).ext` next to the destination.
+    Rename,
+}
+
+/// Whether an action touches the filesystem or only reports what it would do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    DryRun,
+    Execute,
+}
+
+/// Kind of link created by `link_into`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// absolute symbolic link to the matched file.
+    Symlink,
+    Hardlink,
+}
+
+/// Outcome of a file action, one entry per matched file.
+#[derive(Debug, Default)]
+pub struct ActionReport {
+    /// (source, destination), both the same for in-place actions.
+    pub done: Vec<(PathBuf, PathBuf)>,
+    /// sources left alone because of `Collision::Skip`.
+    pub skipped: Vec<PathBuf>,
+    pub failed: Vec<CollectError>,
+    /// sources held open by another process, see `with_lock_policy`.
+    pub locked: Vec<PathBuf>,
+}
+
+impl ActionReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Outcome of `delete`.
+#[derive(Debug, Default)]
+pub struct DeleteReport {
+    /// files that were (or would be) removed.
+    pub deleted: Vec<PathBuf>,
+    /// directories that were (or would be) left empty and removed.
+    pub pruned_dirs: Vec<PathBuf>,
+    pub failed: Vec<CollectError>,
+    /// files held open by another process, see `with_lock_policy`.
+    pub locked: Vec<PathBuf>,
+}
+
+impl DeleteReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// splits out the failures caused by a lock.
+fn take_locked(failed: &mut Vec<CollectError>) -> Vec<PathBuf> {
+    let (locked, rest): (Vec<_>, Vec<_>) = failed.drain(..).partition(|e| is_locked(&e.source));
+    *failed = rest;
+    locked.into_iter().map(|e| e.path).collect()
+}
+
+impl Settle for ActionReport {
+    #[inline]
+    fn take_locked(&mut self) -> Vec<PathBuf> {
+        take_locked(&mut self.failed)
+    }
+    fn absorb(&mut self, retried: Self) {
+        self.done.extend(retried.done);
+        self.skipped.extend(retried.skipped);
+        self.failed.extend(retried.failed);
+        self.locked.extend(retried.locked);
+    }
+    #[inline]
+    fn push_locked(&mut self, locked: Vec<PathBuf>) {
+        self.locked.extend(locked);
+    }
+}
+
+impl Settle for DeleteReport {
+    #[inline]
+    fn take_locked(&mut self) -> Vec<PathBuf> {
+        take_locked(&mut self.failed)
+    }
+    fn absorb(&mut self, retried: Self) {
+        self.deleted.extend(retried.deleted);
+        self.pruned_dirs.extend(retried.pruned_dirs);
+        self.failed.extend(retried.failed);
+        self.locked.extend(retried.locked);
+    }
+    #[inline]
+    fn push_locked(&mut self, locked: Vec<PathBuf>) {
+        self.locked.extend(locked);
+    }
+}
+
+/// Where destructive actions copy the originals first, one timestamped tree per action.
+#[derive(Debug)]
+pub(crate) struct Backup {
+    dir: PathBuf,
+    root_dir: PathBuf,
+}
+
+impl Backup {
+    /// `<backup_dir>/<YYYY-MM-DD_HHMMSS>` (UTC), created on the first copy.
+    pub(crate) fn new(backup_dir: &Path, root_dir: &Path) -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let dir = backup_dir.join(template::timestamp(secs));
+        let dir = if dir.exists() { free_name(&dir) } else { dir };
+        Backup {
+            dir,
+            root_dir: root_dir.to_path_buf(),
+        }
+    }
+    /// copies `path` under the backup tree, keeping its path relative to the root.
+    fn save(&self, path: &Path) -> io::Result<()> {
+        copy_file(path, &rebase(path, &self.root_dir, &self.dir)?)
+    }
+}
+
+/// the step that failed and its error, before the path is attached.
+type Failure = (Operation, io::Error);
+
+/// saves `path` to `backup` if any, then runs `action`, an error of which counts as `op`.
+#[inline]
+fn backed_up<F>(
+    backup: Option<&Backup>,
+    path: &Path,
+    op: Operation,
+    action: F,
+) -> Result<(), Failure>
+where
+    F: FnOnce() -> io::Result<()>,
+{
+    if let Some(backup) = backup {
+        backup.save(path).map_err(|e| (Operation::Backup, e))?;
+    }
+    action().map_err(|e| (op, e))
+}
+
+enum Outcome {
+    Done(PathBuf, PathBuf),
+    Skipped(PathBuf),
+    Failed(CollectError),
+}
+
+impl Outcome {
+    #[inline]
+    fn failed(path: PathBuf, op: Operation, e: io::Error) -> Self {
+        Outcome::Failed(CollectError::new(path, op, e))
+    }
+}
+
+impl FromParallelIterator<Outcome> for ActionReport {
+    fn from_par_iter<I>(iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = Outcome>,
+    {
+        let outcomes: Vec<Outcome> = iter.into_par_iter().collect();
+        let mut report = ActionReport::default();
+        for outcome in outcomes {
+            match outcome {
+                Outcome::Done(src, dest) => report.done.push((src, dest)),
+                Outcome::Skipped(src) => report.skipped.push(src),
+                Outcome::Failed(e) => report.failed.push(e),
+            }
+        }
+        report
+    }
+}
+
+/// `path` re-rooted from `root_dir` onto `dest_root`.
+#[inline]
+fn rebase(path: &Path, root_dir: &Path, dest_root: &Path) -> io::Result<PathBuf> {
+    match path.strip_prefix(root_dir) {
+        Ok(rel) => Ok(dest_root.join(rel)),
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("* outside of the root {}", root_dir.display()),
+        )),
+    }
+}
+
+fn free_name(dest: &Path) -> PathBuf {
+    let stem = dest.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = dest
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| dest.with_file_name(format!("{} ({}){}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .unwrap()
+}
+
+/// applies `collision` to a planned destination, `None` means skip.
+fn resolve(dest: PathBuf, collision: Collision) -> Option<PathBuf> {
+    if !dest.exists() {
+        return Some(dest);
+    }
+    match collision {
+        Collision::Skip => None,
+        Collision::Overwrite => Some(dest),
+        Collision::Rename => Some(free_name(&dest)),
+    }
+}
+
+#[cfg(unix)]
+const EXDEV: i32 = 18;
+#[cfg(windows)]
+const EXDEV: i32 = 17; // ERROR_NOT_SAME_DEVICE
+#[cfg(not(any(unix, windows)))]
+const EXDEV: i32 = -1;
+
+fn move_file(src: &Path, dest: &Path) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::rename(src, dest) {
+        Err(e) if e.raw_os_error() == Some(EXDEV) => {
+            fs::copy(src, dest)?;
+            fs::remove_file(src)
+        }
+        result => result,
+    }
+}
+
+fn copy_file(src: &Path, dest: &Path) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(src, dest).map(|_| ())
+}
+
+pub(crate) fn copy_to(
+    paths: Vec<PathBuf>,
+    root_dir: &Path,
+    dest_root: &Path,
+    collision: Collision,
+) -> ActionReport {
+    paths
+        .into_par_iter()
+        .map(|src| {
+            let dest = match rebase(&src, root_dir, dest_root) {
+                Ok(dest) => dest,
+                Err(e) => return Outcome::failed(src, Operation::Copy, e),
+            };
+            let dest = match resolve(dest, collision) {
+                Some(dest) => dest,
+                None => return Outcome::Skipped(src),
+            };
+            match copy_file(&src, &dest) {
+                Ok(()) => Outcome::Done(src, dest),
+                Err(e) => Outcome::failed(src, Operation::Copy, e),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn move_to(
+    paths: Vec<PathBuf>,
+    root_dir: &Path,
+    dest_root: &Path,
+    collision: Collision,
+    mode: Mode,
+) -> ActionReport {
+    paths
+        .into_par_iter()
+        .map(|src| {
+            let dest = match rebase(&src, root_dir, dest_root) {
+                Ok(dest) => dest,
+                Err(e) => return Outcome::failed(src, Operation::Move, e),
+            };
+            let dest = match resolve(dest, collision) {
+                Some(dest) => dest,
+                None => return Outcome::Skipped(src),
+            };
+            if mode == Mode::DryRun {
+                return Outcome::Done(src, dest);
+            }
+            match move_file(&src, &dest) {
+                Ok(()) => Outcome::Done(src, dest),
+                Err(e) => Outcome::failed(src, Operation::Move, e),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn delete(
+    paths: Vec<PathBuf>,
+    root_dir: &Path,
+    mode: Mode,
+    prune_empty_dirs: bool,
+    backup: Option<&Backup>,
+) -> DeleteReport {
+    let results: Vec<(PathBuf, Result<(), Failure>)> = paths
+        .into_par_iter()
+        .map(|path| {
+            let result = match mode {
+                Mode::DryRun => Ok(()),
+                Mode::Execute => {
+                    backed_up(backup, &path, Operation::Remove, || fs::remove_file(&path))
+                }
+            };
+            (path, result)
+        })
+        .collect();
+
+    let mut report = DeleteReport::default();
+    for (path, result) in results {
+        match result {
+            Ok(()) => report.deleted.push(path),
+            Err((op, e)) => report.failed.push(CollectError::new(path, op, e)),
+        }
+    }
+    if prune_empty_dirs {
+        prune_dirs(&mut report, root_dir, mode);
+    }
+    report
+}
+
+/// `path` is a symlink whose target does not exist.
+fn is_broken_symlink(path: &Path) -> bool {
+    let is_link = fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+    is_link && matches!(fs::metadata(path), Err(e) if e.kind() == io::ErrorKind::NotFound)
+}
+
+/// moves the files to the platform trash one at a time, desktop trash APIs
+/// don't take parallel calls well.
+#[cfg(feature = "trash")]
+pub(crate) fn delete_to_trash(paths: Vec<PathBuf>, mode: Mode) -> DeleteReport {
+    let mut report = DeleteReport::default();
+    for path in paths {
+        let result = match mode {
+            Mode::DryRun => Ok(()),
+            Mode::Execute => trash::delete(&path).map_err(|e| io::Error::other(e.to_string())),
+        };
+        match result {
+            Ok(()) => report.deleted.push(path),
+            Err(e) => report
+                .failed
+                .push(CollectError::new(path, Operation::Remove, e)),
+        }
+    }
+    report
+}
+
+pub(crate) fn remove_broken_symlinks(paths: Vec<PathBuf>, mode: Mode) -> DeleteReport {
+    let results: Vec<(PathBuf, io::Result<()>)> = paths
+        .into_par_iter()
+        .filter(|path| is_broken_symlink(path))
+        .map(|path| {
+            let result = match mode {
+                Mode::DryRun => Ok(()),
+                Mode::Execute => fs::remove_file(&path),
+            };
+            (path, result)
+        })
+        .collect();
+
+    let mut report = DeleteReport::default();
+    for (path, result) in results {
+        match result {
+            Ok(()) => report.deleted.push(path),
+            Err(e) => report
+                .failed
+                .push(CollectError::new(path, Operation::Remove, e)),
+        }
+    }
+    report
+}
+
+pub(crate) fn remove_empty_dirs(
+    root_dir: &Path,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+    mode: Mode,
+) -> DeleteReport {
+    let mut report = DeleteReport::default();
+    empty_dirs(root_dir.to_path_buf(), depth, cfg, mode, &mut report);
+    if let Some(errors) = &cfg.errors {
+        report.failed.append(&mut errors.lock().unwrap());
+    }
+    report
+}
+
+/// removes the empty directories the walk enters below `dir`, deepest first, and tells
+/// whether `dir` is (or would be) left empty. what the walk leaves out, symlinks included,
+/// counts as an entry and is never touched.
+fn empty_dirs(
+    dir: PathBuf,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+    mode: Mode,
+    report: &mut DeleteReport,
+) -> bool {
+    let mut empty = true;
+    for (sub, is_dir) in cfg.read_dir(dir) {
+        let is_link = fs::symlink_metadata(&sub).map_or(true, |m| m.file_type().is_symlink());
+        let depth = match cfg.descend(&sub, depth) {
+            Some(depth) if is_dir && !is_link => depth,
+            _ => {
+                empty = false;
+                continue;
+            }
+        };
+        if !empty_dirs(sub.clone(), depth, cfg, mode, report) {
+            empty = false;
+            continue;
+        }
+        if mode == Mode::Execute {
+            if let Err(e) = fs::remove_dir(&sub) {
+                report
+                    .failed
+                    .push(CollectError::new(sub, Operation::Remove, e));
+                empty = false;
+                continue;
+            }
+        }
+        report.pruned_dirs.push(sub);
+    }
+    empty
+}
+
+/// removes the parents of deleted files that end up empty, deepest first, never the root.
+fn prune_dirs(report: &mut DeleteReport, root_dir: &Path, mode: Mode) {
+    let mut candidates: Vec<PathBuf> = report
+        .deleted
+        .iter()
+        .flat_map(|p| {
+            p.ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(root_dir) && *dir != root_dir)
+                .map(Path::to_path_buf)
+        })
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    candidates.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+
+    let mut gone: HashSet<PathBuf> = report.deleted.iter().cloned().collect();
+    for dir in candidates {
+        let empty = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .map(|entry| entry.map(|e| gone.contains(&e.path())))
+                .all(|gone| matches!(gone, Ok(true))),
+            Err(e) => {
+                report
+                    .failed
+                    .push(CollectError::new(dir, Operation::ReadDir, e));
+                continue;
+            }
+        };
+        if !empty {
+            continue;
+        }
+        if mode == Mode::Execute {
+            if let Err(e) = fs::remove_dir(&dir) {
+                report
+                    .failed
+                    .push(CollectError::new(dir, Operation::Remove, e));
+                continue;
+            }
+        }
+        gone.insert(dir.clone());
+        report.pruned_dirs.push(dir);
+    }
+}
+
+pub(crate) fn apply_rename(
+    paths: Vec<PathBuf>,
+    hooks: &[Hook],
+    mode: Mode,
+    backup: Option<&Backup>,
+) -> ActionReport {
+    let hooked: Vec<(PathBuf, io::Result<Option<PathBuf>>)> = paths
+        .into_par_iter()
+        .map(|src| (src.clone(), hook::run(hooks, src)))
+        .collect();
+    let (mut planned, mut hook_failed) = (Vec::new(), Vec::new());
+    for (src, dest) in hooked {
+        match dest {
+            Ok(Some(dest)) => planned.push((src, dest)),
+            // dropped by a filter-map hook: stays where it is.
+            Ok(None) => planned.push((src.clone(), src)),
+            Err(e) => hook_failed.push(Outcome::failed(src, Operation::Hook, e)),
+        }
+    }
+
+    let mut targets: HashMap<&Path, usize> = HashMap::new();
+    for (_, dest) in planned.iter() {
+        *targets.entry(dest.as_path()).or_default() += 1;
+    }
+    let conflict = |dest: &Path| -> Option<io::Error> {
+        let reason = if targets[dest] > 1 {
+            "* several files are renamed to"
+        } else if dest.symlink_metadata().is_ok() {
+            "* already exists:"
+        } else {
+            return None;
+        };
+        Some(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} {}", reason, dest.display()),
+        ))
+    };
+
+    planned
+        .par_iter()
+        .map(|(src, dest)| {
+            if src == dest {
+                return Outcome::Skipped(src.clone());
+            }
+            if let Some(e) = conflict(dest) {
+                return Outcome::failed(src.clone(), Operation::Rename, e);
+            }
+            let result = match mode {
+                Mode::DryRun => Ok(()),
+                Mode::Execute => backed_up(backup, src, Operation::Rename, || {
+                    dest.parent()
+                        .map_or(Ok(()), fs::create_dir_all)
+                        .and_then(|_| fs::rename(src, dest))
+                }),
+            };
+            match result {
+                Ok(()) => Outcome::Done(src.clone(), dest.clone()),
+                Err((op, e)) => Outcome::failed(src.clone(), op, e),
+            }
+        })
+        .chain(hook_failed)
+        .collect()
+}
+
+fn link_file(src: &Path, dest: &Path, kind: LinkKind) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match kind {
+        LinkKind::Hardlink => fs::hard_link(src, dest),
+        LinkKind::Symlink => {
+            let target = src.canonicalize()?;
+            #[cfg(unix)]
+            return std::os::unix::fs::symlink(target, dest);
+            #[cfg(windows)]
+            return std::os::windows::fs::symlink_file(target, dest);
+            #[cfg(not(any(unix, windows)))]
+            return Err(io::Error::other(format!(
+                "* symlinks are not supported: {}",
+                target.display()
+            )));
+        }
+    }
+}
+
+pub(crate) fn link_into(
+    paths: Vec<PathBuf>,
+    root_dir: &Path,
+    dest_root: &Path,
+    kind: LinkKind,
+) -> ActionReport {
+    paths
+        .into_par_iter()
+        .map(|src| {
+            let dest = match rebase(&src, root_dir, dest_root) {
+                Ok(dest) => dest,
+                Err(e) => return Outcome::failed(src, Operation::Link, e),
+            };
+            match link_file(&src, &dest, kind) {
+                Ok(()) => Outcome::Done(src, dest),
+                Err(e) => Outcome::failed(src, Operation::Link, e),
+            }
+        })
+        .collect()
+}
+
+/// runs `action` on every path in place, its failures counting as `op`.
+fn in_place<F>(paths: Vec<PathBuf>, mode: Mode, op: Operation, action: F) -> ActionReport
+where
+    F: Fn(&Path) -> io::Result<()> + Sync,
+{
+    paths
+        .into_par_iter()
+        .map(|path| {
+            let result = match mode {
+                Mode::DryRun => Ok(()),
+                Mode::Execute => action(&path),
+            };
+            match result {
+                Ok(()) => Outcome::Done(path.clone(), path),
+                Err(e) => Outcome::failed(path, op, e),
+            }
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+pub(crate) fn set_permissions(paths: Vec<PathBuf>, perm: u32, mode: Mode) -> ActionReport {
+    use std::os::unix::fs::PermissionsExt;
+    in_place(paths, mode, Operation::Permissions, |path| {
+        fs::set_permissions(path, fs::Permissions::from_mode(perm))
+    })
+}
+
+#[cfg(unix)]
+pub(crate) fn set_owner(
+    paths: Vec<PathBuf>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    mode: Mode,
+) -> ActionReport {
+    in_place(paths, mode, Operation::Owner, |path| {
+        std::os::unix::fs::chown(path, uid, gid)
+    })
+}
+
+/// writes `contents` next to `path` then renames it over, keeping the permissions.
+fn replace_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    let written = (|| {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::set_permissions(&tmp, fs::metadata(path)?.permissions())?;
+        fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+pub(crate) fn transform_contents<F>(
+    paths: Vec<PathBuf>,
+    transform: F,
+    mode: Mode,
+    backup: Option<&Backup>,
+) -> ActionReport
+where
+    F: Fn(&[u8]) -> io::Result<Vec<u8>> + Sync,
+{
+    paths
+        .into_par_iter()
+        .map(|path| {
+            let transformed = fs::read(&path).and_then(|before| {
+                let after = transform(&before)?;
+                Ok((after != before).then_some(after))
+            });
+            let result = match (transformed, mode) {
+                (Ok(None), _) => return Outcome::Skipped(path),
+                (Ok(Some(_)), Mode::DryRun) => Ok(()),
+                (Ok(Some(after)), Mode::Execute) => {
+                    backed_up(backup, &path, Operation::Write, || {
+                        replace_atomically(&path, &after)
+                    })
+                }
+                (Err(e), _) => Err((Operation::Content, e)),
+            };
+            match result {
+                Ok(()) => Outcome::Done(path.clone(), path),
+                Err((op, e)) => Outcome::failed(path, op, e),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn touch(paths: Vec<PathBuf>, time: SystemTime, mode: Mode) -> ActionReport {
+    in_place(paths, mode, Operation::Times, |path| set_times(path, time))
+}
+
+/// sets both access and modification time of `path` without opening it, so FIFOs
+/// don't block and write-only files don't fail.
+#[cfg(unix)]
+fn set_times(path: &Path, time: SystemTime) -> io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let (tv_sec, tv_nsec) = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => (d.as_secs() as i64, d.subsec_nanos() as i64),
+        Err(e) => {
+            let d = e.duration();
+            match d.subsec_nanos() {
+                0 => (-(d.as_secs() as i64), 0),
+                n => (-(d.as_secs() as i64) - 1, 1_000_000_000 - n as i64),
+            }
+        }
+    };
+    let stamp = libc::timespec {
+        tv_sec: tv_sec as libc::time_t,
+        tv_nsec: tv_nsec as _,
+    };
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: `path` is a valid C string and `times` holds the two entries asked for.
+    match unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), [stamp, stamp].as_ptr(), 0) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// windows needs write access to change attributes, and has no FIFOs to block on.
+#[cfg(not(unix))]
+fn set_times(path: &Path, time: SystemTime) -> io::Result<()> {
+    if !fs::metadata(path)?.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "* only regular files can be touched here",
+        ));
+    }
+    let times = fs::FileTimes::new().set_accessed(time).set_modified(time);
+    fs::OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_times(times)
+}
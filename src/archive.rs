@@ -0,0 +1,173 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    io::{self, Read, Seek, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::content::open_regular;
+
+/// Compression applied around a tar stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+fn append_all<W: Write>(paths: &[PathBuf], root_dir: &Path, writer: W) -> io::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(true);
+    for path in paths {
+        let rel = path.strip_prefix(root_dir).unwrap_or(path);
+        builder
+            .append_path_with_name(path, rel)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    }
+    builder.into_inner()
+}
+
+pub(crate) fn write_tar<W: Write>(
+    mut paths: Vec<PathBuf>,
+    root_dir: &Path,
+    writer: W,
+    compression: TarCompression,
+) -> io::Result<()> {
+    // archives should not depend on thread scheduling.
+    paths.sort();
+    match compression {
+        TarCompression::None => append_all(&paths, root_dir, writer)?.flush(),
+        TarCompression::Gzip => {
+            let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            append_all(&paths, root_dir, encoder)?.finish()?.flush()
+        }
+        TarCompression::Zstd => {
+            let encoder = zstd::Encoder::new(writer, 0)?;
+            append_all(&paths, root_dir, encoder)?.finish()?.flush()
+        }
+    }
+}
+
+/// zip entry name, always `/` separated.
+fn zip_name(prefix: &str, rel: &Path) -> String {
+    let mut name = prefix.trim_matches('/').to_owned();
+    for c in rel.components() {
+        if !name.is_empty() {
+            name.push('/');
+        }
+        name.push_str(&c.as_os_str().to_string_lossy());
+    }
+    name
+}
+
+/// `level` 0 stores files uncompressed, 1..=9 deflates them.
+pub(crate) fn write_zip<W: Write + Seek>(
+    mut paths: Vec<PathBuf>,
+    root_dir: &Path,
+    writer: W,
+    level: u32,
+    prefix: &str,
+) -> io::Result<()> {
+    paths.sort();
+    let options = if level == 0 {
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored)
+    } else {
+        SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(Some(level.min(9) as i64))
+    };
+    let mut zip = ZipWriter::new(writer);
+    for path in paths.iter() {
+        let with_path =
+            |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e));
+        let mut file = open_regular(path).map_err(with_path)?;
+        let meta = file.metadata().map_err(with_path)?;
+        let mut options = options.large_file(meta.len() >= u32::MAX as u64);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(meta.permissions().mode());
+        }
+        let rel = path.strip_prefix(root_dir).unwrap_or(path);
+        zip.start_file(zip_name(prefix, rel), options)?;
+        io::copy(&mut file, &mut zip).map_err(with_path)?;
+    }
+    zip.finish()?.flush()
+}
+
+const ARCHIVE_SUFFIXES: [&str; 5] = [".zip", ".tar", ".tar.gz", ".tgz", ".tar.zst"];
+
+#[inline]
+fn has_archive_suffix(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ARCHIVE_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+#[inline]
+pub(crate) fn is_archive(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| has_archive_suffix(&n.to_string_lossy()))
+}
+
+fn tar_names<R: Read>(reader: R) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in tar::Archive::new(reader).entries()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() {
+            names.push(entry.path()?.to_string_lossy().into_owned());
+        }
+    }
+    Ok(names)
+}
+
+fn entry_names(path: &Path) -> io::Result<Vec<String>> {
+    let file = open_regular(path)?;
+    let name = path.to_string_lossy().to_ascii_lowercase();
+    if name.ends_with(".zip") {
+        let zip = zip::ZipArchive::new(file)?;
+        Ok(zip
+            .file_names()
+            .filter(|n| !n.ends_with('/'))
+            .map(str::to_owned)
+            .collect())
+    } else if name.ends_with(".tar") {
+        tar_names(file)
+    } else if name.ends_with(".tar.zst") {
+        tar_names(zstd::Decoder::new(file)?)
+    } else {
+        tar_names(flate2::read::GzDecoder::new(file))
+    }
+}
+
+/// `name` below `prefix`, `None` for names that would leave it: absolute,
+/// with a `..` or a drive prefix.
+fn entry_path(prefix: &Path, name: &str) -> Option<PathBuf> {
+    let mut path = prefix.to_path_buf();
+    for c in Path::new(name).components() {
+        match c {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::RootDir | Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    (path != prefix).then_some(path)
+}
+
+/// virtual paths of the files inside the archive at `path`.
+/// unreadable archives are treated as plain files without entries,
+/// entries named outside of the archive are left out.
+pub(crate) fn entries(path: &Path) -> Vec<PathBuf> {
+    let mut prefix = path.as_os_str().to_owned();
+    prefix.push("!");
+    let prefix = PathBuf::from(prefix);
+    entry_names(path)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|n| entry_path(&prefix, n))
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,67 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+//! Where a walk reads its directories from. The walk itself (depth, pruning, hooks,
+//! matching, error handling) is the same for every backend.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::WalkConfig;
+
+/// entries of one directory, each with whether it is a directory to walk into.
+pub(crate) type Entries<'a> = Box<dyn Iterator<Item = io::Result<(PathBuf, bool)>> + 'a>;
+
+/// A tree the walk can list.
+pub(crate) trait Backend: Sync {
+    /// the entries of `dir` as they are read, an entry that cannot be read is an `Err`
+    /// among the others.
+    fn read_dir<'a>(&'a self, dir: &Path, cfg: &'a WalkConfig<'_>) -> io::Result<Entries<'a>>;
+    /// whether `path` is a directory, for paths put in place by the error handler.
+    fn is_dir(&self, path: &Path, cfg: &WalkConfig<'_>) -> bool;
+}
+
+/// The local filesystem: symlinks to directories are followed unless they escape
+/// a confined root.
+pub(crate) struct Local;
+
+impl Backend for Local {
+    fn read_dir<'a>(&'a self, dir: &Path, cfg: &'a WalkConfig<'_>) -> io::Result<Entries<'a>> {
+        let read = fs::read_dir(dir)?;
+        Ok(Box::new(read.filter_map(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e)),
+            };
+            let path = entry.path();
+            let is_dir = match entry.file_type() {
+                Ok(t) if !t.is_symlink() => t.is_dir(),
+                _ if cfg.escapes(&path) => return None,
+                _ => cfg.is_dir(&path),
+            };
+            Some(Ok((path, is_dir)))
+        })))
+    }
+    #[inline]
+    fn is_dir(&self, path: &Path, cfg: &WalkConfig<'_>) -> bool {
+        cfg.is_dir(path)
+    }
+}
+
+/// the backend of a walk, the local filesystem unless a remote one is set.
+#[derive(Clone, Copy)]
+pub(crate) struct BackendRef<'b>(pub(crate) &'b dyn Backend);
+
+impl Default for BackendRef<'_> {
+    #[inline]
+    fn default() -> Self {
+        BackendRef(&Local)
+    }
+}
+
+impl fmt::Debug for BackendRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BackendRef")
+    }
+}
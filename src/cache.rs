@@ -0,0 +1,127 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+//! Collected paths stored on disk: a versioned header with the fingerprint of the
+//! configuration that collected them and a checksum of the contents, then the paths
+//! as one zstd frame.
+//!
+//! ```text
+//! magic "CFCACHE\0" | version u32 | platform u8 | fingerprint [u8; 32] | length u64 | blake3 [u8; 32] | zstd
+//! contents: count u64, then every path, each as length u32 + bytes
+//! ```
+
+use std::{
+    convert::TryInto,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::export::path_from_bytes;
+
+const MAGIC: &[u8; 8] = b"CFCACHE\0";
+/// bumped whenever the layout changes, older caches are rebuilt.
+const VERSION: u32 = 2;
+/// encoded `OsStr` bytes only make sense on the platform that wrote them.
+const PLATFORM: u8 = if cfg!(windows) { 1 } else { 0 };
+const HEADER_SIZE: usize = 8 + 4 + 1 + 32 + 8 + 32;
+const LEVEL: i32 = 3;
+
+/// paths that are not valid UTF-8 outside Unix are written too, but never read back.
+fn push_path(buf: &mut Vec<u8>, path: &Path) {
+    let bytes = path.as_os_str().as_encoded_bytes();
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// `f` as an offset from a function of this crate, the same on every run of one build
+/// whatever address the binary is loaded at.
+#[inline]
+pub(crate) fn fn_offset(f: usize) -> usize {
+    f.wrapping_sub(fn_offset as fn(usize) -> usize as usize)
+}
+
+pub(crate) fn write(cache: &Path, fingerprint: &[u8; 32], paths: &[PathBuf]) -> io::Result<()> {
+    let mut contents = Vec::new();
+    contents.extend_from_slice(&(paths.len() as u64).to_le_bytes());
+    for path in paths {
+        push_path(&mut contents, path);
+    }
+
+    let mut out = Vec::with_capacity(HEADER_SIZE + contents.len() / 4);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.push(PLATFORM);
+    out.extend_from_slice(fingerprint);
+    out.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+    out.extend_from_slice(blake3::hash(&contents).as_bytes());
+    zstd::stream::copy_encode(&contents[..], &mut out, LEVEL)?;
+
+    // a reader never sees a half-written cache.
+    let tmp = cache.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(&out)?;
+    file.sync_all()?;
+    fs::rename(&tmp, cache)
+}
+
+/// Reads the fields of `contents` in order.
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(head)
+    }
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+    /// a path, `None` for bytes that are no path here: a crafted or foreign file
+    /// is only ever a cache miss.
+    fn path(&mut self) -> Option<PathBuf> {
+        let len = self.u32()? as usize;
+        path_from_bytes(self.take(len)?)
+    }
+}
+
+/// the paths stored in `cache` by the configuration `fingerprint` identifies, `None` when
+/// it is missing, from another configuration, version or platform, or corrupted.
+pub(crate) fn read(cache: &Path, fingerprint: &[u8; 32]) -> Option<Vec<PathBuf>> {
+    let mut file = File::open(cache).ok()?;
+    let mut header = [0u8; HEADER_SIZE];
+    file.read_exact(&mut header).ok()?;
+    let mut fields = Fields(&header);
+    if fields.take(8)? != MAGIC
+        || fields.u32()? != VERSION
+        || fields.take(1)? != [PLATFORM]
+        || fields.take(32)? != fingerprint
+    {
+        return None;
+    }
+    let len = fields.u64()?;
+    let checksum = fields.take(32)?;
+
+    let mut contents = Vec::with_capacity(len.min(1 << 30) as usize);
+    zstd::stream::read::Decoder::new(file)
+        .ok()?
+        .take(len)
+        .read_to_end(&mut contents)
+        .ok()?;
+    if contents.len() as u64 != len || blake3::hash(&contents).as_bytes() != checksum {
+        return None;
+    }
+
+    let mut fields = Fields(&contents);
+    let count = fields.u64()?;
+    let mut paths = Vec::with_capacity(count.min(len / 4) as usize);
+    for _ in 0..count {
+        paths.push(fields.path()?);
+    }
+    Some(paths)
+}
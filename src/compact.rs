@@ -0,0 +1,262 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    fmt,
+    iter::FromIterator,
+    path::{Path, PathBuf},
+    slice,
+    sync::Arc,
+};
+
+use rayon::prelude::*;
+
+/// Collected paths packed into one byte buffer, for scans too large for a `Vec<PathBuf>`.
+///
+/// Every path costs its bytes plus one offset instead of a separate allocation.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct CompactPaths {
+    bytes: Vec<u8>,
+    ends: Vec<usize>,
+}
+
+impl CompactPaths {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    #[inline]
+    pub fn push<P: AsRef<Path>>(&mut self, path: P) {
+        self.bytes
+            .extend_from_slice(path.as_ref().as_os_str().as_encoded_bytes());
+        self.ends.push(self.bytes.len());
+    }
+    pub fn get(&self, index: usize) -> Option<&Path> {
+        let end = *self.ends.get(index)?;
+        let start = match index {
+            0 => 0,
+            i => self.ends[i - 1],
+        };
+        // SAFETY: the bytes were written by `push` from an `OsStr` on this platform.
+        let name = unsafe { OsStr::from_encoded_bytes_unchecked(&self.bytes[start..end]) };
+        Some(Path::new(name))
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+    /// heap bytes in use, offsets included.
+    #[inline]
+    pub fn heap_size(&self) -> usize {
+        self.bytes.capacity() + self.ends.capacity() * std::mem::size_of::<usize>()
+    }
+    #[inline]
+    pub fn iter(&self) -> CompactIter<'_> {
+        CompactIter {
+            paths: self,
+            next: 0,
+        }
+    }
+    pub fn append(&mut self, other: CompactPaths) {
+        let base = self.bytes.len();
+        self.bytes.extend_from_slice(&other.bytes);
+        self.ends
+            .extend(other.ends.into_iter().map(|end| base + end));
+    }
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.bytes.shrink_to_fit();
+        self.ends.shrink_to_fit();
+    }
+    #[inline]
+    pub fn to_vec(&self) -> Vec<PathBuf> {
+        self.iter().map(Path::to_path_buf).collect()
+    }
+}
+
+impl fmt::Debug for CompactPaths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Extend<PathBuf> for CompactPaths {
+    #[inline]
+    fn extend<I: IntoIterator<Item = PathBuf>>(&mut self, iter: I) {
+        for path in iter {
+            self.push(path);
+        }
+    }
+}
+
+impl FromIterator<PathBuf> for CompactPaths {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = PathBuf>>(iter: I) -> Self {
+        let mut paths = Self::new();
+        paths.extend(iter);
+        paths
+    }
+}
+
+impl FromParallelIterator<PathBuf> for CompactPaths {
+    fn from_par_iter<I: IntoParallelIterator<Item = PathBuf>>(par_iter: I) -> Self {
+        let mut paths = par_iter
+            .into_par_iter()
+            .fold(Self::new, |mut paths, path| {
+                paths.push(path);
+                paths
+            })
+            .reduce(Self::new, |mut a, b| {
+                a.append(b);
+                a
+            });
+        paths.shrink_to_fit();
+        paths
+    }
+}
+
+impl<'a> IntoIterator for &'a CompactPaths {
+    type Item = &'a Path;
+    type IntoIter = CompactIter<'a>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the paths of a `CompactPaths`.
+#[derive(Debug, Clone)]
+pub struct CompactIter<'a> {
+    paths: &'a CompactPaths,
+    next: usize,
+}
+
+impl<'a> Iterator for CompactIter<'a> {
+    type Item = &'a Path;
+    #[inline]
+    fn next(&mut self) -> Option<&'a Path> {
+        let path = self.paths.get(self.next)?;
+        self.next += 1;
+        Some(path)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.paths.len() - self.next;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for CompactIter<'_> {}
+
+/// Collected paths as (parent, file name), every parent directory stored once
+/// and shared by all the files in it.
+#[derive(Debug, Default, Clone)]
+pub struct InternedPaths {
+    parents: HashSet<Arc<Path>>,
+    entries: Vec<(Arc<Path>, OsString)>,
+}
+
+impl InternedPaths {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn push<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+        let (parent, name) = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (parent, name),
+            _ => (Path::new(""), path.as_os_str()),
+        };
+        let parent = self.intern(parent);
+        self.entries.push((parent, name.to_os_string()));
+    }
+    fn intern(&mut self, parent: &Path) -> Arc<Path> {
+        if let Some(shared) = self.parents.get(parent) {
+            return shared.clone();
+        }
+        let shared: Arc<Path> = Arc::from(parent);
+        self.parents.insert(shared.clone());
+        shared
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// number of distinct parent directories.
+    #[inline]
+    pub fn parents_len(&self) -> usize {
+        self.parents.len()
+    }
+    /// (parent, file name) pairs without joining them.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, (Arc<Path>, OsString)> {
+        self.entries.iter()
+    }
+    /// every path joined back together.
+    #[inline]
+    pub fn paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.entries.iter().map(|(parent, name)| parent.join(name))
+    }
+    pub fn append(&mut self, other: InternedPaths) {
+        self.entries.reserve(other.entries.len());
+        for (parent, name) in other.entries {
+            let parent = self.intern(&parent);
+            self.entries.push((parent, name));
+        }
+    }
+    #[inline]
+    pub fn to_vec(&self) -> Vec<PathBuf> {
+        self.paths().collect()
+    }
+}
+
+impl Extend<PathBuf> for InternedPaths {
+    #[inline]
+    fn extend<I: IntoIterator<Item = PathBuf>>(&mut self, iter: I) {
+        for path in iter {
+            self.push(path);
+        }
+    }
+}
+
+impl FromIterator<PathBuf> for InternedPaths {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = PathBuf>>(iter: I) -> Self {
+        let mut paths = Self::new();
+        paths.extend(iter);
+        paths
+    }
+}
+
+impl FromParallelIterator<PathBuf> for InternedPaths {
+    fn from_par_iter<I: IntoParallelIterator<Item = PathBuf>>(par_iter: I) -> Self {
+        par_iter
+            .into_par_iter()
+            .fold(Self::new, |mut paths, path| {
+                paths.push(path);
+                paths
+            })
+            .reduce(Self::new, |mut a, b| {
+                a.append(b);
+                a
+            })
+    }
+}
+
+impl<'a> IntoIterator for &'a InternedPaths {
+    type Item = &'a (Arc<Path>, OsString);
+    type IntoIter = slice::Iter<'a, (Arc<Path>, OsString)>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    error::{CollectError, Operation},
+    hash::HashAlgo,
+};
+
+/// How files present in both trees are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareBy {
+    /// only presence, every common file counts as the same.
+    Presence,
+    /// size and modification time.
+    SizeMtime,
+    /// size, then blake3 of the content.
+    Content,
+}
+
+/// Outcome of `compare`, every path relative to the roots.
+#[derive(Debug, Default)]
+pub struct TreeDiff {
+    pub only_in_a: Vec<PathBuf>,
+    pub only_in_b: Vec<PathBuf>,
+    pub same: Vec<PathBuf>,
+    pub different: Vec<PathBuf>,
+    pub failed: Vec<CollectError>,
+}
+
+impl TreeDiff {
+    #[inline]
+    pub fn is_identical(&self) -> bool {
+        self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.different.is_empty()
+            && self.failed.is_empty()
+    }
+}
+
+fn relative(paths: Vec<PathBuf>, root_dir: &Path) -> BTreeSet<PathBuf> {
+    paths
+        .into_iter()
+        .map(|p| p.strip_prefix(root_dir).map(Path::to_path_buf).unwrap_or(p))
+        .collect()
+}
+
+fn equal(rel: &Path, root_a: &Path, root_b: &Path, by: CompareBy) -> Result<bool, CollectError> {
+    if by == CompareBy::Presence {
+        return Ok(true);
+    }
+    let (a, b) = (root_a.join(rel), root_b.join(rel));
+    let failed = |op| move |e| CollectError::new(rel.to_path_buf(), op, e);
+    let meta_a = fs::metadata(&a).map_err(failed(Operation::Metadata))?;
+    let meta_b = fs::metadata(&b).map_err(failed(Operation::Metadata))?;
+    if meta_a.len() != meta_b.len() {
+        return Ok(false);
+    }
+    match by {
+        CompareBy::SizeMtime => meta_a
+            .modified()
+            .and_then(|m| Ok(m == meta_b.modified()?))
+            .map_err(failed(Operation::Metadata)),
+        _ => HashAlgo::Blake3
+            .hash_file(&a)
+            .and_then(|h| Ok(h == HashAlgo::Blake3.hash_file(&b)?))
+            .map_err(failed(Operation::Content)),
+    }
+}
+
+pub(crate) fn compare(
+    paths_a: Vec<PathBuf>,
+    root_a: &Path,
+    paths_b: Vec<PathBuf>,
+    root_b: &Path,
+    by: CompareBy,
+) -> TreeDiff {
+    let (a, b) = (relative(paths_a, root_a), relative(paths_b, root_b));
+    let mut diff = TreeDiff {
+        only_in_a: a.difference(&b).cloned().collect(),
+        only_in_b: b.difference(&a).cloned().collect(),
+        ..Default::default()
+    };
+    let both: Vec<&PathBuf> = a.intersection(&b).collect();
+    let checked: Vec<(PathBuf, Result<bool, CollectError>)> = both
+        .into_par_iter()
+        .map(|rel| (rel.clone(), equal(rel, root_a, root_b, by)))
+        .collect();
+    for (rel, result) in checked {
+        match result {
+            Ok(true) => diff.same.push(rel),
+            Ok(false) => diff.different.push(rel),
+            Err(e) => diff.failed.push(e),
+        }
+    }
+    diff
+}
@@ -0,0 +1,329 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    error::{CollectError, Operation},
+    locked::LockPolicy,
+};
+
+/// bytes inspected by the binary heuristic.
+const SNIFF_SIZE: usize = 8 * 1024;
+
+/// Kind of content kept by `with_text_only` / `with_binary_only`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ContentClass {
+    Text,
+    Binary,
+}
+
+/// Probable text encoding reported by `collect_encodings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// plain ASCII is reported as UTF-8.
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    /// anything else without NUL bytes, which is also how windows-1252 looks.
+    Latin1,
+}
+
+/// Result of the content passes (`collect_hashed`, `collect_contents`, ..): what was
+/// read, and the files that could not be. Special files (FIFOs, sockets, devices) are
+/// never read, they are reported as errors.
+#[derive(Debug)]
+pub struct ContentReport<T> {
+    pub files: Vec<(PathBuf, T)>,
+    pub errors: Vec<CollectError>,
+}
+
+impl<T> Default for ContentReport<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<T> ContentReport<T> {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// runs `read` on every path under `lock`, files it returns `None` for are left out.
+pub(crate) fn read_each<T, F>(paths: Vec<PathBuf>, lock: LockPolicy, read: F) -> ContentReport<T>
+where
+    T: Send,
+    F: Fn(&Path) -> io::Result<Option<T>> + Sync,
+{
+    let results: Vec<(PathBuf, io::Result<Option<T>>)> = paths
+        .into_par_iter()
+        .map(|path| {
+            let result = lock.run(|| read(&path)).map(Option::flatten);
+            (path, result)
+        })
+        .collect();
+    let mut report = ContentReport::default();
+    for (path, result) in results {
+        match result {
+            Ok(Some(v)) => report.files.push((path, v)),
+            Ok(None) => {}
+            Err(e) => report
+                .errors
+                .push(CollectError::new(path, Operation::Content, e)),
+        }
+    }
+    report
+}
+
+/// opens `path` for reading if it is a regular file. FIFOs, sockets and devices are an
+/// error: a FIFO without a writer blocks the open and every read after it.
+pub(crate) fn open_regular(path: &Path) -> io::Result<File> {
+    // non-blocking, so a FIFO is opened at once and refused below.
+    #[cfg(unix)]
+    let file = {
+        use std::os::unix::fs::OpenOptionsExt;
+        std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?
+    };
+    #[cfg(not(unix))]
+    let file = File::open(path)?;
+    if !file.metadata()?.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("* not a regular file: {}", path.display()),
+        ));
+    }
+    Ok(file)
+}
+
+/// reads up to `n` leading bytes of `path`.
+pub(crate) fn read_prefix(path: &Path, n: usize) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(n.min(SNIFF_SIZE));
+    open_regular(path)?.take(n as u64).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// same heuristic as git and grep: a NUL byte means binary.
+#[inline]
+fn looks_binary(prefix: &[u8]) -> bool {
+    prefix.contains(&0)
+}
+
+/// text means no NUL byte and valid UTF-8, a sequence cut by the sniff limit is fine.
+fn looks_text(prefix: &[u8]) -> bool {
+    if looks_binary(prefix) {
+        return false;
+    }
+    match std::str::from_utf8(prefix) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && prefix.len() == SNIFF_SIZE,
+    }
+}
+
+/// lines as `str::lines` counts them, `None` for binary files.
+fn count_lines(path: &Path, max_bytes: u64) -> io::Result<Option<u64>> {
+    let file = open_regular(path)?;
+    if file.metadata()?.len() > max_bytes {
+        return Ok(None);
+    }
+    let mut reader = io::BufReader::new(file);
+    let mut buf = vec![0u8; SNIFF_SIZE];
+    let (mut lines, mut last, mut first) = (0u64, b'\n', true);
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if first && looks_binary(&buf[..n]) {
+            return Ok(None);
+        }
+        first = false;
+        lines += buf[..n].iter().filter(|b| **b == b'\n').count() as u64;
+        last = buf[n - 1];
+    }
+    Ok(Some(if last == b'\n' { lines } else { lines + 1 }))
+}
+
+pub(crate) fn line_counts(
+    paths: Vec<PathBuf>,
+    max_bytes: u64,
+    lock: LockPolicy,
+) -> ContentReport<u64> {
+    read_each(paths, lock, |path| count_lines(path, max_bytes))
+}
+
+/// `mime` matches `pattern` exactly or through a `type/*` wildcard.
+#[inline]
+fn mime_matches(mime: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some("*") => true,
+        Some(top) => mime.split('/').next() == Some(top),
+        None => mime == pattern,
+    }
+}
+
+/// the sniffed prefix of `path`, `None` when it cannot be read: a filter leaves it out.
+#[inline]
+fn sniff(path: &Path, lock: LockPolicy) -> Option<Vec<u8>> {
+    lock.run(|| read_prefix(path, SNIFF_SIZE)).ok().flatten()
+}
+
+/// whether the sniffed type of `path` is one of `mimes`.
+pub(crate) fn is_mime(path: &Path, mimes: &[String], lock: LockPolicy) -> bool {
+    let prefix = match sniff(path, lock) {
+        Some(prefix) => prefix,
+        None => return false,
+    };
+    match infer::get(&prefix) {
+        Some(kind) => mimes.iter().any(|m| mime_matches(kind.mime_type(), m)),
+        None => false,
+    }
+}
+
+pub(crate) fn filter_mime(paths: Vec<PathBuf>, mimes: &[String], lock: LockPolicy) -> Vec<PathBuf> {
+    paths
+        .into_par_iter()
+        .filter(|path| is_mime(path, mimes, lock))
+        .collect()
+}
+
+/// whether `path` looks like `class`.
+pub(crate) fn is_class(path: &Path, class: ContentClass, lock: LockPolicy) -> bool {
+    match sniff(path, lock) {
+        Some(prefix) => looks_text(&prefix) == (class == ContentClass::Text),
+        None => false,
+    }
+}
+
+pub(crate) fn filter_class(
+    paths: Vec<PathBuf>,
+    class: ContentClass,
+    lock: LockPolicy,
+) -> Vec<PathBuf> {
+    paths
+        .into_par_iter()
+        .filter(|path| is_class(path, class, lock))
+        .collect()
+}
+
+/// BOM first, then NUL placement for UTF-16 without BOM, `None` for binaries.
+fn detect_encoding(prefix: &[u8]) -> Option<Encoding> {
+    if prefix.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some(Encoding::Utf8Bom);
+    }
+    if prefix.starts_with(&[0xFF, 0xFE]) {
+        return Some(Encoding::Utf16Le);
+    }
+    if prefix.starts_with(&[0xFE, 0xFF]) {
+        return Some(Encoding::Utf16Be);
+    }
+    if looks_binary(prefix) {
+        let pairs = prefix.len() / 2;
+        let even = prefix.iter().step_by(2).filter(|b| **b == 0).count();
+        let odd = prefix
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|b| **b == 0)
+            .count();
+        // mostly-ASCII UTF-16 has a NUL in every other byte.
+        return if pairs > 0 && odd * 10 >= pairs * 9 && even == 0 {
+            Some(Encoding::Utf16Le)
+        } else if pairs > 0 && even * 10 >= pairs * 9 && odd == 0 {
+            Some(Encoding::Utf16Be)
+        } else {
+            None
+        };
+    }
+    if looks_text(prefix) {
+        Some(Encoding::Utf8)
+    } else {
+        Some(Encoding::Latin1)
+    }
+}
+
+pub(crate) fn encodings(paths: Vec<PathBuf>, lock: LockPolicy) -> ContentReport<Encoding> {
+    read_each(paths, lock, |path| {
+        read_prefix(path, SNIFF_SIZE).map(|prefix| detect_encoding(&prefix))
+    })
+}
+
+/// reads whole files up to `limit_bytes` each, skipping those that would overrun `budget_bytes`.
+pub(crate) fn contents(
+    paths: Vec<PathBuf>,
+    limit_bytes: u64,
+    budget_bytes: u64,
+    lock: LockPolicy,
+) -> ContentReport<Vec<u8>> {
+    let used = AtomicU64::new(0);
+    read_each(paths, lock, |path| {
+        let file = open_regular(path)?;
+        let len = file.metadata()?.len();
+        if len > limit_bytes {
+            return Ok(None);
+        }
+        if used.fetch_add(len, Ordering::Relaxed) + len > budget_bytes {
+            used.fetch_sub(len, Ordering::Relaxed);
+            return Ok(None);
+        }
+        let mut buf = Vec::with_capacity(len as usize);
+        let read = file.take(len).read_to_end(&mut buf);
+        if read.is_err() {
+            used.fetch_sub(len, Ordering::Relaxed);
+        }
+        read.map(|_| Some(buf))
+    })
+}
+
+/// maps every file read-only, files that cannot be mapped (special files, empty
+/// files on some platforms) are reported.
+#[cfg(feature = "mmap")]
+pub(crate) fn mmaps(paths: Vec<PathBuf>, lock: LockPolicy) -> ContentReport<memmap2::Mmap> {
+    read_each(paths, lock, |path| {
+        let file = open_regular(path)?;
+        // SAFETY: not guaranteed here, a file truncated or rewritten by anyone while mapped
+        // is undefined behavior. `collect_mmaps` leaves keeping the files unchanged to the caller.
+        unsafe { memmap2::Mmap::map(&file) }.map(Some)
+    })
+}
+
+/// the first `n` bytes of every file, skipping those that would take the total over `budget_bytes`.
+pub(crate) fn previews(
+    paths: Vec<PathBuf>,
+    n: usize,
+    budget_bytes: u64,
+    lock: LockPolicy,
+) -> ContentReport<Vec<u8>> {
+    let used = AtomicU64::new(0);
+    read_each(paths, lock, |path| {
+        let file = open_regular(path)?;
+        let len = file.metadata()?.len().min(n as u64);
+        if used.fetch_add(len, Ordering::Relaxed) + len > budget_bytes {
+            used.fetch_sub(len, Ordering::Relaxed);
+            return Ok(None);
+        }
+        let mut buf = Vec::with_capacity(len as usize);
+        let read = file.take(len).read_to_end(&mut buf);
+        // what was reserved but not read, a file shrunk meanwhile or the error.
+        let unread = len - read.as_ref().map_or(0, |_| buf.len() as u64);
+        used.fetch_sub(unread, Ordering::Relaxed);
+        read.map(|_| Some(buf))
+    })
+}
@@ -0,0 +1,126 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    fmt::{self, Write},
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::export::{path_bytes, path_from_bytes};
+
+const HEADER: &str = "collectfiles-cursor 2";
+
+/// (directory, remaining depth below it)
+pub(crate) type Pending = (PathBuf, Option<usize>);
+
+/// Directories still to be read by a resumable collection.
+///
+/// `Display` / `FromStr` round-trip it through a plain text form
+/// so it can be saved between process runs. Paths keep their exact bytes:
+/// `\\`, control characters and bytes that are not UTF-8 are written as `\\xHH`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub(crate) pending: Vec<Pending>,
+}
+
+impl Cursor {
+    #[inline]
+    pub(crate) fn new(root_dir: PathBuf, depth: Option<usize>) -> Self {
+        Self {
+            pending: vec![(root_dir, depth)],
+        }
+    }
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+    #[inline]
+    pub fn pending(&self) -> impl Iterator<Item = &PathBuf> {
+        self.pending.iter().map(|(dir, _)| dir)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for (dir, depth) in self.pending.iter() {
+            match depth {
+                Some(dep) => write!(f, "{}", dep)?,
+                None => write!(f, "-")?,
+            }
+            f.write_char('\t')?;
+            write_escaped(f, dir)?;
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = io::Error;
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid =
+            |line: &str| io::Error::new(io::ErrorKind::InvalidData, format!("* cursor: {}", line));
+        let mut lines = s.lines();
+        match lines.next() {
+            Some(HEADER) => {}
+            other => return Err(invalid(other.unwrap_or_default())),
+        }
+        let mut pending = Vec::new();
+        for line in lines.filter(|l| !l.is_empty()) {
+            let (depth, dir) = line.split_once('\t').ok_or_else(|| invalid(line))?;
+            let depth = match depth {
+                "-" => None,
+                dep => Some(dep.parse().map_err(|_| invalid(line))?),
+            };
+            let dir = unescape(dir).ok_or_else(|| invalid(line))?;
+            pending.push((dir, depth));
+        }
+        Ok(Self { pending })
+    }
+}
+
+/// `path` with `\\`, control characters and bytes that are not UTF-8 as `\\xHH`,
+/// so it stays on one line and comes back byte for byte.
+fn write_escaped(f: &mut fmt::Formatter<'_>, path: &Path) -> fmt::Result {
+    for chunk in path_bytes(path).utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                c if c.is_ascii_control() => write!(f, "\\x{:02x}", c as u8)?,
+                c => f.write_char(c)?,
+            }
+        }
+        for b in chunk.invalid() {
+            write!(f, "\\x{:02x}", b)?;
+        }
+    }
+    Ok(())
+}
+
+/// inverse of `write_escaped`.
+fn unescape(s: &str) -> Option<PathBuf> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match rest {
+            [b'\\', tail @ ..] => {
+                bytes.push(b'\\');
+                rest = tail;
+            }
+            [b'x', hi, lo, tail @ ..] => {
+                let digit = |d: u8| (d as char).to_digit(16);
+                bytes.push((digit(*hi)? * 16 + digit(*lo)?) as u8);
+                rest = tail;
+            }
+            _ => return None,
+        }
+    }
+    path_from_bytes(&bytes)
+}
@@ -0,0 +1,107 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::limit::Semaphore;
+
+/// directories read at once on a spinning disk, parallel seeks only slow it down.
+const ROTATIONAL_READERS: usize = 1;
+/// directories read at once on a network mount.
+const NETWORK_READERS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+enum Kind {
+    Solid,
+    Rotational,
+    Network,
+}
+
+/// Per-device concurrency limits, worked out the first time a device is seen.
+#[derive(Debug, Default)]
+pub(crate) struct Devices {
+    limits: Mutex<HashMap<u64, Option<Arc<Semaphore>>>>,
+}
+
+impl Devices {
+    /// the semaphore of the device holding `dir`, `None` when it needs no limit.
+    pub(crate) fn limit_for(&self, dir: &Path) -> Option<Arc<Semaphore>> {
+        let dev = device_id(dir)?;
+        let mut limits = self.limits.lock().unwrap();
+        limits
+            .entry(dev)
+            .or_insert_with(|| {
+                let readers = match kind(dev) {
+                    Kind::Solid => return None,
+                    Kind::Rotational => ROTATIONAL_READERS,
+                    Kind::Network => NETWORK_READERS,
+                };
+                Some(Arc::new(Semaphore::new(readers)))
+            })
+            .clone()
+    }
+}
+
+#[cfg(unix)]
+fn device_id(dir: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(dir).ok().map(|m| m.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_dir: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn kind(dev: u64) -> Kind {
+    use std::fs;
+
+    const NETWORK_FS: &[&str] = &[
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+        "smbfs",
+        "9p",
+        "afs",
+        "ceph",
+        "glusterfs",
+        "lustre",
+        "fuse.sshfs",
+    ];
+    // same split as glibc's major() / minor().
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    let id = format!("{}:{}", major, minor);
+
+    // mountinfo: `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw`
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo").unwrap_or_default();
+    let fs_type = mountinfo.lines().find_map(|line| {
+        let (mount, fs) = line.split_once(" - ")?;
+        if mount.split(' ').nth(2)? != id {
+            return None;
+        }
+        fs.split(' ').next()
+    });
+    if fs_type.is_some_and(|t| NETWORK_FS.contains(&t)) {
+        return Kind::Network;
+    }
+    // partitions keep `queue/` on their parent disk.
+    let rotational = ["queue/rotational", "../queue/rotational"]
+        .iter()
+        .find_map(|f| fs::read_to_string(format!("/sys/dev/block/{}/{}", id, f)).ok());
+    match rotational.as_deref().map(str::trim) {
+        Some("1") => Kind::Rotational,
+        _ => Kind::Solid,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn kind(_dev: u64) -> Kind {
+    Kind::Solid
+}
@@ -0,0 +1,111 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs,
+    hash::Hash as StdHash,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    content,
+    hash::{Hash, HashAlgo},
+};
+
+const PARTIAL_SIZE: u64 = 4 * 1024;
+
+/// splits every group by `key`, keeping only groups with more than one member.
+/// Files `key` cannot be worked out for (broken links, unreadable or vanished files) are left out.
+fn regroup<K, F>(groups: Vec<Vec<PathBuf>>, key: F) -> Vec<Vec<PathBuf>>
+where
+    K: StdHash + Eq + Send,
+    F: Fn(&PathBuf) -> io::Result<K> + Sync,
+{
+    groups
+        .into_par_iter()
+        .flat_map(|group| {
+            let keyed: Vec<(K, PathBuf)> = group
+                .into_par_iter()
+                .filter_map(|p| key(&p).ok().map(|k| (k, p)))
+                .collect();
+            let mut map: HashMap<K, Vec<PathBuf>> = HashMap::new();
+            for (k, p) in keyed {
+                map.entry(k).or_default().push(p);
+            }
+            map.into_iter()
+                .filter(|(_, v)| v.len() > 1)
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// How far `collect_duplicates_by` compares files, each stage only looking at
+/// the groups the previous one left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DupeStage {
+    /// same size, nothing is read.
+    Size,
+    /// same size and same first and last 4 KiB.
+    Edges,
+    /// same contents. (default)
+    Full,
+}
+
+/// hash of the first and last `PARTIAL_SIZE` bytes, the whole file when shorter.
+fn hash_edges(path: &Path) -> io::Result<(Hash, Option<Hash>)> {
+    let mut file = content::open_regular(path)?;
+    let len = file.metadata()?.len();
+    let head = HashAlgo::Blake3.hash_reader((&mut file).take(PARTIAL_SIZE))?;
+    if len <= PARTIAL_SIZE {
+        return Ok((head, None));
+    }
+    // files under 8 KiB: only what the head left.
+    file.seek(SeekFrom::Start((len - PARTIAL_SIZE).max(PARTIAL_SIZE)))?;
+    let tail = HashAlgo::Blake3.hash_reader(file.take(PARTIAL_SIZE))?;
+    Ok((head, Some(tail)))
+}
+
+/// size of a regular file, an error for FIFOs, sockets and devices: they report
+/// a size of 0 and opening one could block.
+fn regular_len(path: &Path) -> io::Result<u64> {
+    let meta = fs::metadata(path)?;
+    if !meta.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("* not a regular file: {}", path.display()),
+        ));
+    }
+    Ok(meta.len())
+}
+
+pub(crate) fn find_duplicates(paths: Vec<PathBuf>, stage: DupeStage) -> Vec<Vec<PathBuf>> {
+    let mut groups = regroup(vec![paths], |p| regular_len(p));
+    if stage >= DupeStage::Edges {
+        groups = regroup(groups, |p| hash_edges(p));
+    }
+    if stage >= DupeStage::Full {
+        groups = regroup(groups, |p| HashAlgo::Blake3.hash_file(p));
+    }
+    groups.par_iter_mut().for_each(|g| g.sort());
+    groups.sort();
+    groups
+}
+
+/// groups of files sharing a file name, each from a different directory.
+pub(crate) fn find_name_collisions(paths: Vec<PathBuf>) -> Vec<Vec<PathBuf>> {
+    let mut map: HashMap<OsString, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        if let Some(name) = path.file_name() {
+            map.entry(name.to_os_string()).or_default().push(path);
+        }
+    }
+    let mut groups: Vec<Vec<PathBuf>> = map.into_values().filter(|g| g.len() > 1).collect();
+    groups.par_iter_mut().for_each(|g| g.sort());
+    groups.sort();
+    groups
+}
@@ -0,0 +1,201 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashSet,
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+/// A collected file with its metadata, from `collect_entries`.
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    /// where the file is, the symlink target under `with_resolve_symlinks`.
+    pub path: PathBuf,
+    /// the symlink the walk found, when `path` was resolved from one.
+    pub symlink: Option<PathBuf>,
+    /// of a symlink itself unless `with_dereference`.
+    pub metadata: Metadata,
+}
+
+impl FileEntry {
+    /// apparent size, what `len()` reports.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.metadata.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.metadata.len() == 0
+    }
+    /// bytes actually taken on disk, `st_blocks * 512` on Unix and the apparent size elsewhere.
+    #[inline]
+    pub fn allocated(&self) -> u64 {
+        allocated(&self.metadata)
+    }
+    /// fewer bytes allocated than the file claims to hold.
+    #[inline]
+    pub fn is_sparse(&self) -> bool {
+        self.allocated() < self.len()
+    }
+}
+
+#[cfg(unix)]
+impl FileEntry {
+    /// device holding the file.
+    #[inline]
+    pub fn dev(&self) -> u64 {
+        std::os::unix::fs::MetadataExt::dev(&self.metadata)
+    }
+    #[inline]
+    pub fn ino(&self) -> u64 {
+        std::os::unix::fs::MetadataExt::ino(&self.metadata)
+    }
+    /// number of hard links to the file.
+    #[inline]
+    pub fn nlink(&self) -> u64 {
+        std::os::unix::fs::MetadataExt::nlink(&self.metadata)
+    }
+    /// `(dev, ino)`, identical for every hard link of one file.
+    #[inline]
+    pub fn file_id(&self) -> (u64, u64) {
+        (self.dev(), self.ino())
+    }
+}
+
+#[cfg(unix)]
+#[inline]
+fn allocated(meta: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::blocks(meta) * 512
+}
+
+#[cfg(not(unix))]
+#[inline]
+fn allocated(meta: &Metadata) -> u64 {
+    meta.len()
+}
+
+/// whether `path` has holes, see `FileEntry::is_sparse`.
+#[inline]
+pub(crate) fn is_sparse(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| allocated(&m) < m.len())
+}
+
+/// keeps the files with holes.
+pub(crate) fn filter_sparse(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    paths.into_par_iter().filter(|p| is_sparse(p)).collect()
+}
+
+/// (target, the link it came from) of every path, broken links staying as they are.
+pub(crate) fn resolve(paths: Vec<PathBuf>) -> Vec<(PathBuf, Option<PathBuf>)> {
+    let resolved: Vec<(PathBuf, Option<PathBuf>)> = paths
+        .into_par_iter()
+        .map(|path| {
+            let is_link = fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink());
+            match is_link.then(|| fs::canonicalize(&path)) {
+                Some(Ok(target)) => (strip_verbatim(target), Some(path)),
+                _ => (path, None),
+            }
+        })
+        .collect();
+    // several links to one file report it once.
+    let mut seen = HashSet::new();
+    resolved
+        .into_iter()
+        .filter(|(path, _)| seen.insert(path.clone()))
+        .collect()
+}
+
+/// `canonicalize` answers `\\?\C:\..` and `\\?\UNC\server\share\..` on Windows,
+/// turned back into the `C:\..` and `\\server\share\..` the walk reports.
+#[cfg(windows)]
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let text = match path.to_str() {
+        Some(text) => text,
+        None => return path,
+    };
+    if let Some(unc) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", unc))
+    } else if let Some(local) = text.strip_prefix(r"\\?\") {
+        // only drive paths survive without the prefix.
+        match local.as_bytes() {
+            [_, b':', ..] => PathBuf::from(local),
+            _ => path,
+        }
+    } else {
+        path
+    }
+}
+
+#[cfg(not(windows))]
+#[inline]
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    path
+}
+
+/// the entries of `paths`, leaving out files gone since the walk.
+/// `dereference` reads the metadata of symlink targets (`ls -lL`) instead of
+/// the links themselves (`ls -l`); broken links always report their own.
+pub(crate) fn entries(paths: Vec<(PathBuf, Option<PathBuf>)>, dereference: bool) -> Vec<FileEntry> {
+    paths
+        .into_par_iter()
+        .filter_map(|(path, symlink)| {
+            let metadata = if dereference {
+                fs::metadata(&path).or_else(|_| fs::symlink_metadata(&path))
+            } else {
+                fs::symlink_metadata(&path)
+            }
+            .ok()?;
+            Some(FileEntry {
+                path,
+                symlink,
+                metadata,
+            })
+        })
+        .collect()
+}
+
+/// One entry flattened for the table exports.
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+pub(crate) struct Row {
+    pub(crate) path: String,
+    pub(crate) size: u64,
+    /// since the epoch.
+    pub(crate) mtime: Option<std::time::Duration>,
+    /// `file`, `symlink`, `dir` or `other`.
+    pub(crate) kind: &'static str,
+    /// hex digest.
+    pub(crate) hash: Option<String>,
+}
+
+/// the rows of `entries`, hashed in parallel when `algo` is set.
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+pub(crate) fn rows(entries: Vec<FileEntry>, algo: Option<crate::HashAlgo>) -> Vec<Row> {
+    entries
+        .into_par_iter()
+        .map(|e| {
+            let t = e.metadata.file_type();
+            let kind = if t.is_symlink() {
+                "symlink"
+            } else if t.is_file() {
+                "file"
+            } else if t.is_dir() {
+                "dir"
+            } else {
+                "other"
+            };
+            Row {
+                path: e.path.to_string_lossy().into_owned(),
+                size: e.metadata.len(),
+                mtime: e
+                    .metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()),
+                kind,
+                hash: algo.and_then(|a| a.hash_file(&e.path).ok().map(|h| h.to_hex())),
+            }
+        })
+        .collect()
+}
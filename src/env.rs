@@ -0,0 +1,47 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::env;
+
+/// walking threads, a positive number.
+pub(crate) const THREADS: &str = "COLLECTFILES_THREADS";
+/// `1` / `true` / `yes` or `0` / `false` / `no`.
+pub(crate) const SKIP_HIDDEN: &str = "COLLECTFILES_SKIP_HIDDEN";
+/// comma separated directory names never entered.
+pub(crate) const EXCLUDE: &str = "COLLECTFILES_EXCLUDE";
+
+/// the trimmed value of `name`, `None` when unset or blank.
+fn var(name: &str) -> Option<String> {
+    let value = env::var(name).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+pub(crate) fn threads() -> Option<usize> {
+    let value = var(THREADS)?;
+    match value.parse() {
+        Ok(n) if n > 0 => Some(n),
+        _ => panic!("* {}: not a positive number: {}", THREADS, value),
+    }
+}
+
+pub(crate) fn skip_hidden() -> Option<bool> {
+    let value = var(SKIP_HIDDEN)?;
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => panic!("* {}: not a boolean: {}", SKIP_HIDDEN, value),
+    }
+}
+
+pub(crate) fn exclude() -> Vec<String> {
+    var(EXCLUDE)
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,82 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{error, fmt, io, path::PathBuf};
+
+/// What was being done when a `CollectError` happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    ReadDir,
+    Metadata,
+    Hook,
+    Content,
+    /// the path is longer than `with_max_path_len` allows.
+    PathLength,
+    Copy,
+    Move,
+    Remove,
+    Rename,
+    Link,
+    Permissions,
+    Owner,
+    /// setting access and modification times, `touch`.
+    Times,
+    /// writing the transformed contents back.
+    Write,
+    /// saving the original to `with_backup_dir` first.
+    Backup,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::ReadDir => "read_dir",
+            Operation::Metadata => "metadata",
+            Operation::Hook => "hook",
+            Operation::Content => "content",
+            Operation::PathLength => "path length",
+            Operation::Copy => "copy",
+            Operation::Move => "move",
+            Operation::Remove => "remove",
+            Operation::Rename => "rename",
+            Operation::Link => "link",
+            Operation::Permissions => "permissions",
+            Operation::Owner => "owner",
+            Operation::Times => "times",
+            Operation::Write => "write",
+            Operation::Backup => "backup",
+        })
+    }
+}
+
+/// An `io::Error` with the path and the operation it came from.
+#[derive(Debug)]
+pub struct CollectError {
+    pub path: PathBuf,
+    pub op: Operation,
+    pub source: io::Error,
+}
+
+impl CollectError {
+    #[inline]
+    pub(crate) fn new(path: PathBuf, op: Operation, source: io::Error) -> Self {
+        Self { path, op, source }
+    }
+}
+
+impl fmt::Display for CollectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.op, self.path.display(), self.source)
+    }
+}
+
+impl error::Error for CollectError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<CollectError> for io::Error {
+    fn from(e: CollectError) -> Self {
+        io::Error::new(e.source.kind(), e)
+    }
+}
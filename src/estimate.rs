@@ -0,0 +1,93 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{match_file, sample::Rng, WalkConfig};
+
+/// random walks from the root averaged by `estimate`.
+const PROBES: u64 = 64;
+
+/// Rough size of a collection, predicted without walking the whole tree.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// expected number of matched files.
+    pub files: f64,
+    /// expected total size of the matched files.
+    pub bytes: f64,
+}
+
+impl Estimate {
+    /// `files` rounded, to size a buffer for `collect_into`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.files.round() as usize
+    }
+    /// share of the estimate already seen, for progress bars. (0.0..=1.0)
+    #[inline]
+    pub fn progress(&self, files_seen: usize) -> f64 {
+        if self.files <= 0.0 {
+            return 1.0;
+        }
+        (files_seen as f64 / self.files).min(1.0)
+    }
+}
+
+/// files of `dir` the walk would match and the subdirectories it would enter, with
+/// their remaining depth.
+fn level(
+    dir: &Path,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+) -> (Estimate, Vec<(PathBuf, Option<usize>)>) {
+    let (mut found, mut subdirs) = (Estimate::default(), Vec::new());
+    // through the walk, so its open-directory, device and throttle limits hold.
+    for (path, is_dir) in cfg.read_dir(dir.to_path_buf()) {
+        if is_dir {
+            if let Ok(depth) = cfg.plan(&path, depth) {
+                subdirs.push((path, depth));
+            }
+            continue;
+        }
+        if let Some(path) = match_file(path, cfg) {
+            found.files += 1.0;
+            found.bytes += cfg.retry(|| fs::metadata(&path)).map_or(0, |m| m.len()) as f64;
+        }
+    }
+    (found, subdirs)
+}
+
+/// one random path down from the root's subdirectories, every level weighted by the
+/// branching above it. (Knuth)
+fn probe(mut subdirs: Vec<(PathBuf, Option<usize>)>, cfg: &WalkConfig, rng: &mut Rng) -> Estimate {
+    let (mut found, mut weight) = (Estimate::default(), 1.0);
+    while !subdirs.is_empty() {
+        weight *= subdirs.len() as f64;
+        let (dir, depth) = subdirs.swap_remove(rng.below(subdirs.len()));
+        let (here, below) = level(&dir, depth, cfg);
+        found.files += weight * here.files;
+        found.bytes += weight * here.bytes;
+        subdirs = below;
+    }
+    found
+}
+
+/// the root is read once, every probe starts below it.
+pub(crate) fn estimate(root_dir: &Path, depth: Option<usize>, cfg: &WalkConfig) -> Estimate {
+    let (root, subdirs) = level(root_dir, depth, cfg);
+    let total = (0..PROBES)
+        .into_par_iter()
+        .map(|i| probe(subdirs.clone(), cfg, &mut Rng::from_clock(i)))
+        .reduce(Estimate::default, |a, b| Estimate {
+            files: a.files + b.files,
+            bytes: a.bytes + b.bytes,
+        });
+    Estimate {
+        files: root.files + total.files / PROBES as f64,
+        bytes: root.bytes + total.bytes / PROBES as f64,
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{Traversal, WalkConfig};
+
+/// directories read by the sampling run of `explain`.
+const SAMPLE_DIRS: usize = 256;
+/// pruned directories reported at most.
+const SAMPLE_PRUNED: usize = 16;
+
+/// Why a directory is left out of the walk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruneReason {
+    /// below the depth limit, or the depth override of a parent.
+    DepthLimit,
+    /// `with_dir_hook` answered `DirAction::Skip`.
+    DirHook,
+    /// `with_depth_prune` returned true.
+    DepthPrune,
+    /// a dot-directory under `with_skip_hidden`.
+    Hidden,
+    /// its name is in `with_prune_dirs`.
+    Name,
+    /// excluded by an `.ignore` file.
+    Ignored,
+    /// a kernel pseudo filesystem like `/proc`, under `collect_volumes`.
+    PseudoFs,
+    /// longer than `with_max_path_len`.
+    PathTooLong,
+    /// it could not be read, with the error.
+    Unreadable(String),
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneReason::DepthLimit => f.write_str("depth limit"),
+            PruneReason::DirHook => f.write_str("dir hook"),
+            PruneReason::DepthPrune => f.write_str("depth prune"),
+            PruneReason::Hidden => f.write_str("hidden"),
+            PruneReason::Name => f.write_str("pruned name"),
+            PruneReason::Ignored => f.write_str(".ignore"),
+            PruneReason::PseudoFs => f.write_str("pseudo filesystem"),
+            PruneReason::PathTooLong => f.write_str("path too long"),
+            PruneReason::Unreadable(e) => write!(f, "unreadable: {}", e),
+        }
+    }
+}
+
+/// Effective configuration of a collector, returned by `explain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explain {
+    pub root: PathBuf,
+    /// deepest level reported, the root's entries being level 1. `None` for no limit.
+    pub max_depth: Option<usize>,
+    pub target_regex: Option<String>,
+    /// (directory regex, levels below it)
+    pub depth_overrides: Vec<(String, usize)>,
+    pub dir_hook: bool,
+    pub depth_prune: bool,
+    pub skip_hidden: bool,
+    pub ignore_files: bool,
+    /// directory names never descended into.
+    pub prune_dirs: Vec<String>,
+    /// symlinked directories are walked into.
+    pub follow_symlinks: bool,
+    /// symlinks out of the root are not followed.
+    pub confine_to_root: bool,
+    pub traversal: Traversal,
+    pub stable_order: bool,
+    /// post-walk filters in the order they run.
+    pub filters: Vec<String>,
+    pub hooks: usize,
+    /// the first directories the walk leaves out, from a short sampling run.
+    pub pruned: Vec<(PathBuf, PruneReason)>,
+}
+
+impl fmt::Display for Explain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "root: {}", self.root.display())?;
+        match self.max_depth {
+            Some(depth) => writeln!(f, "depth: 1..={}", depth)?,
+            None => writeln!(f, "depth: unlimited")?,
+        }
+        if let Some(regex) = &self.target_regex {
+            writeln!(f, "target regex: {}", regex)?;
+        }
+        for (regex, depth) in self.depth_overrides.iter() {
+            writeln!(f, "depth override: {} -> {}", regex, depth)?;
+        }
+        writeln!(
+            f,
+            "prunes: dir hook {}, depth prune {}",
+            on_off(self.dir_hook),
+            on_off(self.depth_prune)
+        )?;
+        writeln!(f, "skip hidden: {}", on_off(self.skip_hidden))?;
+        writeln!(f, ".ignore files: {}", on_off(self.ignore_files))?;
+        if !self.prune_dirs.is_empty() {
+            writeln!(f, "pruned names: {}", self.prune_dirs.join(", "))?;
+        }
+        writeln!(
+            f,
+            "follow symlinks: {}, confined to root {}",
+            on_off(self.follow_symlinks),
+            on_off(self.confine_to_root)
+        )?;
+        writeln!(
+            f,
+            "traversal: {:?}, stable order {}",
+            self.traversal,
+            on_off(self.stable_order)
+        )?;
+        for filter in self.filters.iter() {
+            writeln!(f, "filter: {}", filter)?;
+        }
+        writeln!(f, "hooks: {}", self.hooks)?;
+        for (dir, reason) in self.pruned.iter() {
+            writeln!(f, "pruned: {} ({})", dir.display(), reason)?;
+        }
+        Ok(())
+    }
+}
+
+#[inline]
+fn on_off(yes: bool) -> &'static str {
+    if yes {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// walks breadth first until enough directories were read or pruned.
+pub(crate) fn sample_pruned(
+    root_dir: &Path,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+) -> Vec<(PathBuf, PruneReason)> {
+    let mut pruned = Vec::new();
+    let mut queue = VecDeque::from(vec![(root_dir.to_path_buf(), depth)]);
+    let mut read = 0;
+    while let Some((dir, depth)) = queue.pop_front() {
+        if read == SAMPLE_DIRS || pruned.len() >= SAMPLE_PRUNED {
+            break;
+        }
+        read += 1;
+        let mut entries = cfg.read_dir(dir);
+        if let Some(errors) = &cfg.errors {
+            for e in errors.lock().unwrap().drain(..) {
+                pruned.push((e.path, PruneReason::Unreadable(e.source.to_string())));
+            }
+        }
+        entries.sort();
+        for (sub, _) in entries.into_iter().filter(|(_, is_dir)| *is_dir) {
+            match cfg.plan(&sub, depth) {
+                Ok(depth) => queue.push_back((sub, depth)),
+                Err(reason) => pruned.push((sub, reason)),
+            }
+        }
+    }
+    pruned.truncate(SAMPLE_PRUNED);
+    pruned
+}
@@ -0,0 +1,53 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// raw bytes of `path`, lossy only where the platform has no byte representation.
+pub(crate) fn path_bytes(path: &Path) -> std::borrow::Cow<'_, [u8]> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        std::borrow::Cow::Borrowed(path.as_os_str().as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        match path.to_string_lossy() {
+            std::borrow::Cow::Borrowed(s) => std::borrow::Cow::Borrowed(s.as_bytes()),
+            std::borrow::Cow::Owned(s) => std::borrow::Cow::Owned(s.into_bytes()),
+        }
+    }
+}
+
+/// inverse of `path_bytes`, checked: `None` for bytes that are not valid UTF-8 where
+/// the platform has no byte representation.
+pub(crate) fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(PathBuf::from(std::ffi::OsStr::from_bytes(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        std::str::from_utf8(bytes).ok().map(PathBuf::from)
+    }
+}
+
+/// `rsync --files-from` list, `--from0` style when `nul` is set.
+pub(crate) fn write_files_from<W: Write>(
+    mut paths: Vec<PathBuf>,
+    root_dir: &Path,
+    mut writer: W,
+    nul: bool,
+) -> io::Result<()> {
+    paths.sort();
+    let sep: &[u8] = if nul { b"\0" } else { b"\n" };
+    for path in paths.iter() {
+        let rel = path.strip_prefix(root_dir).unwrap_or(path);
+        writer.write_all(&path_bytes(rel))?;
+        writer.write_all(sep)?;
+    }
+    writer.flush()
+}
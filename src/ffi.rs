@@ -0,0 +1,190 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+//! C interface, declared in `include/collectfiles.h`.
+//!
+//! Build a C library with `cargo rustc --release --features ffi --crate-type cdylib`
+//! (or `staticlib`). No panic crosses the boundary: a failed collection returns null.
+
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use regex::Regex;
+
+use crate::{private::CollectFilesConfigured, CollectFilesPrelude};
+
+/// Opaque handle on a configured collection.
+pub struct CollectFilesConfig(CollectFilesConfigured);
+
+/// the string behind `s`, `None` for null or invalid UTF-8.
+///
+/// # Safety
+/// `s` is null or a NUL-terminated string.
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+/// replaces the configuration behind `config` with `f` of it.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`.
+unsafe fn update(
+    config: *mut CollectFilesConfig,
+    f: impl FnOnce(CollectFilesConfigured) -> CollectFilesConfigured,
+) -> c_int {
+    let config = match config.as_mut() {
+        Some(config) => config,
+        None => return -1,
+    };
+    let old = std::mem::take(&mut config.0);
+    config.0 = f(old);
+    0
+}
+
+/// A collection rooted at `root`, null if `root` is null or not UTF-8.
+/// Free it with `collectfiles_free`.
+///
+/// # Safety
+/// `root` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_new(root: *const c_char) -> *mut CollectFilesConfig {
+    match str_arg(root) {
+        Some(root) => Box::into_raw(Box::new(CollectFilesConfig(CollectFilesConfigured::new(
+            PathBuf::from(root),
+        )))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// `config` is null or came from `collectfiles_new` and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_free(config: *mut CollectFilesConfig) {
+    if !config.is_null() {
+        drop(Box::from_raw(config));
+    }
+}
+
+/// `with_max_depth`. 0 on success, -1 for a null config.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_set_max_depth(
+    config: *mut CollectFilesConfig,
+    n: usize,
+) -> c_int {
+    update(config, |c| c.with_max_depth(n))
+}
+
+/// `with_target_regex`. 0 on success, -1 for a null config or an invalid regex.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`, `regex` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_set_target_regex(
+    config: *mut CollectFilesConfig,
+    regex: *const c_char,
+) -> c_int {
+    match str_arg(regex) {
+        Some(regex) if Regex::new(regex).is_ok() => update(config, |c| c.with_target_regex(regex)),
+        _ => -1,
+    }
+}
+
+/// `with_skip_hidden`, any non-zero `yes` turning it on. 0 on success, -1 for a null config.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_set_skip_hidden(
+    config: *mut CollectFilesConfig,
+    yes: c_int,
+) -> c_int {
+    update(config, |c| c.with_skip_hidden(yes != 0))
+}
+
+/// `with_ignore_files`, any non-zero `yes` turning it on. 0 on success, -1 for a null config.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_set_ignore_files(
+    config: *mut CollectFilesConfig,
+    yes: c_int,
+) -> c_int {
+    update(config, |c| c.with_ignore_files(yes != 0))
+}
+
+/// `with_prune_dirs` with one more name. 0 on success, -1 for a null argument.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`, `name` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_add_prune_dir(
+    config: *mut CollectFilesConfig,
+    name: *const c_char,
+) -> c_int {
+    match str_arg(name) {
+        Some(name) => update(config, |c| c.with_prune_dirs(&[name])),
+        None => -1,
+    }
+}
+
+/// Runs the collection: an array of `*len` paths, to be released with
+/// `collectfiles_free_paths`. Unreadable directories are skipped.
+/// Null when `config` is null or the walk panicked.
+///
+/// # Safety
+/// `config` is null or came from `collectfiles_new`, `len` points to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_collect(
+    config: *const CollectFilesConfig,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    *len = 0;
+    let config = match config.as_ref() {
+        Some(config) => config,
+        None => return ptr::null_mut(),
+    };
+    let paths = match panic::catch_unwind(AssertUnwindSafe(|| config.0.try_collect().paths)) {
+        Ok(paths) => paths,
+        Err(_) => return ptr::null_mut(),
+    };
+    let strings: Box<[*mut c_char]> = paths
+        .iter()
+        .filter_map(|p| c_path(p))
+        .map(CString::into_raw)
+        .collect();
+    *len = strings.len();
+    Box::into_raw(strings) as *mut *mut c_char
+}
+
+/// # Safety
+/// `paths` and `len` are exactly what `collectfiles_collect` returned, freed once.
+#[no_mangle]
+pub unsafe extern "C" fn collectfiles_free_paths(paths: *mut *mut c_char, len: usize) {
+    if paths.is_null() {
+        return;
+    }
+    let strings = Box::from_raw(ptr::slice_from_raw_parts_mut(paths, len));
+    for s in strings.iter() {
+        drop(CString::from_raw(*s));
+    }
+}
+
+/// the raw bytes on Unix, UTF-8 elsewhere. Paths holding a NUL are left out.
+fn c_path(path: &Path) -> Option<CString> {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().into_owned().into_bytes();
+    CString::new(bytes).ok()
+}
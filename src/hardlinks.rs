@@ -0,0 +1,121 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+pub(crate) fn dedupe(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    use rayon::prelude::*;
+    use std::{collections::HashMap, fs, os::unix::fs::MetadataExt};
+
+    // only entries with more than one link can collide, broken links and files
+    // gone since they were listed are kept as they are.
+    let keys: Vec<Option<(u64, u64)>> = paths
+        .par_iter()
+        .map(|p| match fs::metadata(p) {
+            Ok(meta) if meta.nlink() > 1 => Some((meta.dev(), meta.ino())),
+            _ => None,
+        })
+        .collect();
+
+    // keep the smallest path of each inode so the result is stable between runs.
+    let mut winners: HashMap<(u64, u64), usize> = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        if let Some(key) = key {
+            let w = winners.entry(*key).or_insert(i);
+            if paths[i] < paths[*w] {
+                *w = i;
+            }
+        }
+    }
+
+    paths
+        .into_iter()
+        .zip(keys)
+        .enumerate()
+        .filter(|(i, (_, key))| match key {
+            Some(key) => winners[key] == *i,
+            None => true,
+        })
+        .map(|(_, (p, _))| p)
+        .collect()
+}
+
+#[cfg(not(unix))]
+#[inline]
+pub(crate) fn dedupe(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    paths
+}
+
+/// whether `path` has at least `min` hard links, `false` once it vanished.
+#[cfg(unix)]
+pub(crate) fn has_nlink(path: &Path, min: u64) -> bool {
+    use std::{fs, os::unix::fs::MetadataExt};
+
+    fs::metadata(path).is_ok_and(|m| m.nlink() >= min)
+}
+
+/// link counts are not known here, every file counts as one link.
+#[cfg(not(unix))]
+#[inline]
+pub(crate) fn has_nlink(_path: &Path, min: u64) -> bool {
+    min <= 1
+}
+
+/// keeps the files with at least `min` hard links, vanished ones dropped.
+pub(crate) fn filter_nlink(paths: Vec<PathBuf>, min: u64) -> Vec<PathBuf> {
+    use rayon::prelude::*;
+
+    paths
+        .into_par_iter()
+        .filter(|p| has_nlink(p, min))
+        .collect()
+}
+
+/// drops paths differing from an earlier one only in letter case, keeping the
+/// smallest spelling. The two must also be the same file, so distinct files of a
+/// case-sensitive filesystem survive.
+pub(crate) fn dedupe_case(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    use std::collections::HashMap;
+
+    paths.sort();
+    let mut kept: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut keep = vec![true; paths.len()];
+    for (i, path) in paths.iter().enumerate() {
+        let spellings = kept
+            .entry(path.to_string_lossy().to_lowercase())
+            .or_default();
+        if spellings.iter().any(|&j| same_file(&paths[j], path)) {
+            keep[i] = false;
+        } else {
+            spellings.push(i);
+        }
+    }
+    paths
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(p, keep)| keep.then_some(p))
+        .collect()
+}
+
+#[cfg(unix)]
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    use std::{fs, os::unix::fs::MetadataExt};
+
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => (a.dev(), a.ino()) == (b.dev(), b.ino()),
+        _ => false,
+    }
+}
+
+/// no inode to compare: the canonical path is read back from an open handle
+/// (`GetFinalPathNameByHandle` on Windows) with the casing stored on disk, so both
+/// spellings of one file give the same. Unsure means different, both are kept.
+#[cfg(not(unix))]
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    use std::fs;
+
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
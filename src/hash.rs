@@ -0,0 +1,289 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    content::{self, ContentReport},
+    export::{path_bytes, path_from_bytes},
+    locked::LockPolicy,
+};
+
+const BUF_SIZE: usize = 64 * 1024;
+
+/// Content hash algorithm used by `collect_hashed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    Blake3,
+    Sha256,
+}
+
+/// 32-byte content digest of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hash(pub [u8; 32]);
+
+impl Hash {
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+    #[inline]
+    pub fn to_hex(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0.iter() {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Hash {
+    type Err = io::Error;
+    fn from_str(hex: &str) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("* hash: {}", hex));
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let mut bytes = [0u8; 32];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Hash(bytes))
+    }
+}
+
+/// Result of `verify_manifest`, every path relative to the root.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// listed and unchanged.
+    pub ok: Vec<PathBuf>,
+    /// listed but not collected.
+    pub missing: Vec<PathBuf>,
+    /// listed with a different hash.
+    pub modified: Vec<PathBuf>,
+    /// collected but not listed.
+    pub extra: Vec<PathBuf>,
+}
+
+impl VerifyReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl HashAlgo {
+    /// Hashes everything `reader` yields.
+    pub fn hash_reader<R: Read>(self, mut reader: R) -> io::Result<Hash> {
+        let mut buf = vec![0u8; BUF_SIZE];
+        match self {
+            HashAlgo::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            hasher.update(&buf[..n]);
+                        }
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(Hash(*hasher.finalize().as_bytes()))
+            }
+            HashAlgo::Sha256 => {
+                let mut hasher = Sha256::new();
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => hasher.update(&buf[..n]),
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(Hash(hasher.finalize().into()))
+            }
+        }
+    }
+    /// Hashes the contents of a regular file, FIFOs, sockets and devices are an error.
+    #[inline]
+    pub fn hash_file<P: AsRef<Path>>(self, path: P) -> io::Result<Hash> {
+        self.hash_reader(content::open_regular(path.as_ref())?)
+    }
+}
+
+pub(crate) fn hash_files(
+    paths: Vec<PathBuf>,
+    algo: HashAlgo,
+    lock: LockPolicy,
+) -> ContentReport<Hash> {
+    content::read_each(paths, lock, |path| algo.hash_file(path).map(Some))
+}
+
+/// `sha256sum` style line with the raw bytes of the path, escaped the same way
+/// coreutils does it.
+fn manifest_line(hash: &Hash, path: &Path) -> Vec<u8> {
+    let name = path_bytes(path);
+    let escaped = name.contains(&b'\\') || name.contains(&b'\n');
+    let mut line = Vec::with_capacity(name.len() + 68);
+    if escaped {
+        line.push(b'\\');
+    }
+    line.extend_from_slice(hash.to_string().as_bytes());
+    line.extend_from_slice(b"  ");
+    for &b in name.iter() {
+        match b {
+            b'\\' if escaped => line.extend_from_slice(b"\\\\"),
+            b'\n' if escaped => line.extend_from_slice(b"\\n"),
+            b => line.push(b),
+        }
+    }
+    line.push(b'\n');
+    line
+}
+
+pub(crate) fn write_manifest<W: Write>(
+    paths: Vec<PathBuf>,
+    root_dir: &Path,
+    algo: HashAlgo,
+    lock: LockPolicy,
+    mut writer: W,
+) -> io::Result<()> {
+    let root_dir = root_dir.to_path_buf();
+    let (tx, rx) = mpsc::channel();
+    let worker = crate::spawn_worker(move || {
+        // stops taking new files once the receiver hung up after an error.
+        let _ = paths.into_par_iter().try_for_each_with(tx, |tx, path| {
+            let hash = lock.run(|| algo.hash_file(&path));
+            let rel = path.strip_prefix(&root_dir).unwrap_or(&path).to_path_buf();
+            tx.send((rel, hash))
+        });
+    });
+    let written = rx.iter().try_for_each(|(rel, hash)| {
+        match hash {
+            Ok(Some(hash)) => writer.write_all(&manifest_line(&hash, &rel)),
+            // locked and skipped, see `with_lock_policy`.
+            Ok(None) => Ok(()),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("{}: {}", rel.display(), e),
+            )),
+        }
+    });
+    drop(rx);
+    if let Some(worker) = worker {
+        worker
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e));
+    }
+    written?;
+    writer.flush()
+}
+
+/// inverse of `manifest_line`.
+fn parse_manifest_line(line: &[u8]) -> io::Result<(Hash, PathBuf)> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("* manifest: {}", String::from_utf8_lossy(line)),
+        )
+    };
+    let (escaped, rest) = match line.strip_prefix(b"\\") {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    if rest.len() < 66 {
+        return Err(invalid());
+    }
+    let hash = std::str::from_utf8(&rest[..64])
+        .map_err(|_| invalid())?
+        .parse()?;
+    let name = match &rest[64..66] {
+        b"  " | b" *" => &rest[66..],
+        _ => return Err(invalid()),
+    };
+    let name = if escaped {
+        let mut out = Vec::with_capacity(name.len());
+        let mut bytes = name.iter();
+        while let Some(&b) = bytes.next() {
+            if b != b'\\' {
+                out.push(b);
+                continue;
+            }
+            match bytes.next() {
+                Some(b'\\') => out.push(b'\\'),
+                Some(b'n') => out.push(b'\n'),
+                _ => return Err(invalid()),
+            }
+        }
+        out
+    } else {
+        name.to_vec()
+    };
+    Ok((hash, path_from_bytes(&name).ok_or_else(invalid)?))
+}
+
+pub(crate) fn verify_manifest<R: BufRead>(
+    paths: Vec<PathBuf>,
+    root_dir: &Path,
+    algo: HashAlgo,
+    reader: R,
+) -> io::Result<VerifyReport> {
+    let mut listed = HashMap::new();
+    for line in reader.split(b'\n') {
+        let line = line?;
+        // `lines` would have taken the `\r` of a CRLF manifest off too.
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.is_empty() {
+            continue;
+        }
+        let (hash, rel) = parse_manifest_line(line)?;
+        listed.insert(rel, hash);
+    }
+
+    let collected: Vec<(PathBuf, PathBuf)> = paths
+        .into_iter()
+        .map(|p| (p.strip_prefix(root_dir).unwrap_or(&p).to_path_buf(), p))
+        .collect();
+
+    let mut report = VerifyReport::default();
+    let checked: Vec<io::Result<(PathBuf, Option<bool>)>> = collected
+        .into_par_iter()
+        .map(|(rel, path)| match listed.get(&rel) {
+            Some(expected) => algo
+                .hash_file(&path)
+                .map(|hash| (rel.clone(), Some(hash == *expected)))
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", rel.display(), e))),
+            None => Ok((rel, None)),
+        })
+        .collect();
+    for result in checked {
+        let (rel, same) = result?;
+        listed.remove(&rel);
+        match same {
+            Some(true) => report.ok.push(rel),
+            Some(false) => report.modified.push(rel),
+            None => report.extra.push(rel),
+        }
+    }
+    report.missing = listed.into_keys().collect();
+
+    report.ok.sort();
+    report.missing.sort();
+    report.modified.sort();
+    report.extra.sort();
+    Ok(report)
+}
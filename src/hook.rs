@@ -0,0 +1,49 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{io, path::PathBuf};
+
+use regex::Regex;
+
+use crate::template::Template;
+
+/// One link of the hook chain, run in registration order.
+#[derive(Debug, Clone)]
+pub(crate) enum Hook {
+    Map(fn(PathBuf) -> PathBuf),
+    FilterMap(fn(PathBuf) -> Option<PathBuf>),
+    Try(fn(PathBuf) -> io::Result<PathBuf>),
+    /// (old root, new prefix)
+    Rebase(PathBuf, PathBuf),
+    /// (regex, template, root the expanded relative paths are joined to)
+    Template(Regex, String, PathBuf),
+    Tokens(Template),
+}
+
+/// `Ok(None)` when a filter-map hook dropped the path.
+pub(crate) fn run(hooks: &[Hook], mut path: PathBuf) -> io::Result<Option<PathBuf>> {
+    for hook in hooks {
+        path = match hook {
+            Hook::Map(f) => f(path),
+            Hook::FilterMap(f) => match f(path) {
+                Some(p) => p,
+                None => return Ok(None),
+            },
+            Hook::Try(f) => f(path)?,
+            Hook::Rebase(old, new) => match path.strip_prefix(old) {
+                Ok(rel) => new.join(rel),
+                Err(_) => path,
+            },
+            Hook::Tokens(template) => template.apply(&path)?,
+            Hook::Template(regex, template, root) => match path.to_str().and_then(|s| {
+                let caps = regex.captures(s)?;
+                let mut expanded = String::new();
+                caps.expand(template, &mut expanded);
+                Some(root.join(expanded))
+            }) {
+                Some(p) => p,
+                None => path,
+            },
+        };
+    }
+    Ok(Some(path))
+}
@@ -0,0 +1,167 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use regex::Regex;
+
+/// name of the ignore files, the convention of ripgrep and fd.
+const IGNORE_FILE: &str = ".ignore";
+
+/// One pattern line of an ignore file.
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    /// `!pattern`, re-includes what an earlier rule excluded.
+    negated: bool,
+    /// `pattern/`, only matches directories.
+    dir_only: bool,
+    /// the pattern has a `/`, matched against the path relative to the
+    /// ignore file instead of the file name alone.
+    anchored: bool,
+}
+
+/// The `.ignore` files met during one walk, each read once.
+#[derive(Debug, Default)]
+pub(crate) struct Ignores {
+    files: RwLock<HashMap<PathBuf, Option<Arc<Vec<Rule>>>>>,
+}
+
+impl Ignores {
+    /// whether `path` is excluded by the ignore files of `root` and the directories between.
+    /// the deepest file wins, and within a file the last matching line.
+    pub(crate) fn is_ignored(&self, root: &Path, path: &Path, is_dir: bool) -> bool {
+        let parent = match path.parent() {
+            Some(parent) if parent.starts_with(root) => parent,
+            _ => return false,
+        };
+        let mut dirs: Vec<&Path> = parent
+            .ancestors()
+            .take_while(|d| d.starts_with(root))
+            .collect();
+        dirs.reverse();
+        let mut ignored = false;
+        for dir in dirs {
+            let rules = match self.rules(dir) {
+                Some(rules) => rules,
+                None => continue,
+            };
+            let rel = relative(dir, path);
+            let name = rel.rsplit('/').next().unwrap_or(&rel);
+            for rule in rules.iter() {
+                if rule.dir_only && !is_dir {
+                    continue;
+                }
+                let subject = if rule.anchored { rel.as_str() } else { name };
+                if rule.regex.is_match(subject) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+    fn rules(&self, dir: &Path) -> Option<Arc<Vec<Rule>>> {
+        if let Some(rules) = self.files.read().unwrap().get(dir) {
+            return rules.clone();
+        }
+        // read without holding the lock, a racing thread reading the same file is harmless.
+        let rules = fs::read_to_string(dir.join(IGNORE_FILE))
+            .ok()
+            .map(|text| Arc::new(text.lines().filter_map(parse).collect()));
+        self.files
+            .write()
+            .unwrap()
+            .entry(dir.to_path_buf())
+            .or_insert(rules)
+            .clone()
+    }
+}
+
+/// `path` below `dir` with `/` separators.
+fn relative(dir: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(dir).unwrap_or(path);
+    rel.iter()
+        .map(|c| c.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn parse(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let line = line.strip_prefix('/').unwrap_or(line);
+    let regex = Regex::new(&format!("^{}$", glob_to_regex(line))).ok()?;
+    Some(Rule {
+        regex,
+        negated,
+        dir_only,
+        anchored,
+    })
+}
+
+/// gitignore glob syntax: `*`, `?`, `[...]` and `**` across directories.
+fn glob_to_regex(glob: &str) -> String {
+    let mut out = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                let mut class = String::from("[");
+                if let Some(&'!') = chars.peek() {
+                    chars.next();
+                    class.push('^');
+                }
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        class.push('\\');
+                    }
+                    class.push(c);
+                }
+                if closed {
+                    out.push_str(&class);
+                    out.push(']');
+                } else {
+                    out.push_str(&regex::escape(&class));
+                }
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    out.push_str(&regex::escape(&c.to_string()));
+                }
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    out
+}
@@ -22,34 +22,577 @@
 //! ```
 
 use std::{
-    fs, io,
+    fs,
+    io::{self, BufRead, Write},
     path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
 };
 
-use rayon::prelude::*;
+use rayon::{iter::Either, prelude::*};
 use regex::Regex;
 
+mod access;
+mod actions;
+#[cfg(feature = "archive")]
+mod archive;
+mod backend;
+#[cfg(feature = "cache")]
+mod cache;
+mod compact;
+mod compare;
+mod content;
+mod cursor;
+mod device;
+mod dupes;
+mod entry;
+mod env;
+mod error;
+mod estimate;
+mod explain;
+mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod hardlinks;
+mod hash;
+mod hook;
+mod ignore;
+mod limit;
+mod locked;
+#[cfg(unix)]
+mod openat;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod preset;
+#[cfg(feature = "python")]
+mod python;
+mod report;
+mod sample;
+mod set;
+#[cfg(feature = "sftp")]
+mod sftp;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stream;
+mod template;
+mod visit;
+mod volume;
+pub use access::AccessReport;
+pub use actions::{ActionReport, Collision, DeleteReport, LinkKind, Mode};
+#[cfg(feature = "archive")]
+pub use archive::TarCompression;
+pub use compact::{CompactIter, CompactPaths, InternedPaths};
+pub use compare::{CompareBy, TreeDiff};
+pub use content::{ContentReport, Encoding};
+pub use cursor::Cursor;
+pub use dupes::DupeStage;
+pub use entry::FileEntry;
+pub use error::{CollectError, Operation};
+pub use estimate::Estimate;
+pub use explain::{Explain, PruneReason};
+pub use hash::{Hash, HashAlgo, VerifyReport};
+pub use locked::LockPolicy;
+#[cfg(feature = "mmap")]
+pub use memmap2::Mmap;
+pub use report::{Bucket, ExtSummary};
+pub use set::PathSet;
+pub use stream::DirBatches;
+pub use visit::FileVisitor;
+pub use volume::volumes;
+
+/// Result of `try_collect`: what was collected and what failed on the way.
+#[derive(Debug, Default)]
+pub struct CollectReport {
+    pub paths: Vec<PathBuf>,
+    pub errors: Vec<CollectError>,
+}
+
+/// Order in which directories are visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Traversal {
+    /// recurse into each directory as soon as it is found. (default)
+    #[default]
+    Dfs,
+    /// finish every directory of a level before the next one, shallow results come first.
+    Bfs,
+}
+
+/// What to do with a directory about to be entered, answered by `with_dir_hook`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirAction {
+    /// enter it as usual, following the configured depth.
+    Descend,
+    /// leave it and everything below it out.
+    Skip,
+    /// enter it with `n` more levels below it, whatever the configured depth says.
+    DescendWithDepth(usize),
+}
+
+/// Answer of `with_error_handler` to a directory that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// leave the directory (or the entry that failed to be listed) out.
+    Skip,
+    /// read the directory again, the handler is asked again if that fails too.
+    Retry,
+    /// panic with the path and the error.
+    Abort,
+    /// read this directory instead, or report this path instead of the failed entry.
+    Substitute(PathBuf),
+}
+
 pub trait CollectFilesPrelude {
     fn as_root_dir(&self) -> &Path;
     fn as_target_regex(&self) -> Option<&str>;
     fn as_hook(&self) -> Option<fn(PathBuf) -> PathBuf>;
     fn as_depth(&self) -> Option<usize>;
+    /// Hooks compose, every `with_*hook` runs after the ones registered before it.
     fn with_hook(self, hook_fn: fn(PathBuf) -> PathBuf) -> CollectFilesConfigured;
+    /// Levels of subdirectories entered below the root, `0` reads the root alone.
     fn with_depth(self, level: usize) -> CollectFilesConfigured;
+    /// Reports entries at most `n` levels deep, the root's own entries being level 1,
+    /// `0` reports nothing. `with_max_depth(n + 1)` is `with_depth(n)`.
+    fn with_max_depth(self, n: usize) -> CollectFilesConfigured;
     fn with_target_regex(self, regex: &str) -> CollectFilesConfigured;
+    /// Replaces an unreadable directory with the one `f` returns,
+    /// returning `PathBuf::new()` skips it instead.
     fn with_unwrap_or_else(self, f: fn(io::Error) -> PathBuf) -> CollectFilesConfigured;
+    /// Reports files reached through several hard links only once. (Unix)
+    fn with_dedupe_hardlinks(self, yes: bool) -> CollectFilesConfigured;
     fn collect(&self) -> Vec<PathBuf>;
+    /// Hashes the contents of every matched file in parallel, files that cannot be read
+    /// are reported in `errors`. Paths are the on-disk paths, the hook is not applied.
+    fn collect_hashed(&self, algo: HashAlgo) -> ContentReport<Hash>;
+    /// Groups of matched files with identical content.
+    /// (size, then first and last 4 KiB hash, then full hash)
+    /// Files that cannot be read and special files (FIFOs, sockets, devices) are left
+    /// out of the groups.
+    fn collect_duplicates(&self) -> Vec<Vec<PathBuf>>;
+    /// `collect_duplicates` stopping after `stage`, trading certainty for speed on
+    /// large sets: `DupeStage::Size` reads no contents, `DupeStage::Edges` 8 KiB per file.
+    fn collect_duplicates_by(&self, stage: DupeStage) -> Vec<Vec<PathBuf>>;
+    /// Groups of matched files sharing the same file name in different directories.
+    /// Names are compared as-is, contents are never read.
+    fn collect_name_collisions(&self) -> Vec<Vec<PathBuf>>;
+    /// Writes a `sha256sum -c` compatible manifest (`<hex>  <path>`) of the matched files,
+    /// paths relative to the root, streaming lines as files are hashed. Paths are written
+    /// as raw bytes, the first error stops the hashing, locked files follow `with_lock_policy`.
+    /// A special file (FIFO, socket, device) is an error, it is never opened.
+    fn write_manifest<W: Write>(&self, writer: W, algo: HashAlgo) -> io::Result<()>;
+    /// Re-hashes the files listed in a manifest written by `write_manifest`
+    /// and reports missing, modified and extra files.
+    fn verify_manifest<R: BufRead>(&self, reader: R, algo: HashAlgo) -> io::Result<VerifyReport>;
+    /// Copies the matched files under `dest_root`, keeping their path relative to the root.
+    fn copy_to<P: AsRef<Path>>(&self, dest_root: P, collision: Collision) -> ActionReport;
+    /// Moves the matched files under `dest_root`, falling back to copy and remove
+    /// across devices.
+    fn move_to<P: AsRef<Path>>(
+        &self,
+        dest_root: P,
+        collision: Collision,
+        mode: Mode,
+    ) -> ActionReport;
+    /// Removes the matched files, optionally pruning directories left empty.
+    /// `Mode::DryRun` only reports what would be removed.
+    fn delete(&self, mode: Mode, prune_empty_dirs: bool) -> DeleteReport;
+    /// Unlinks the matched symlinks whose target no longer exists.
+    /// `Mode::DryRun` only reports what would be removed.
+    fn remove_broken_symlinks(&self, mode: Mode) -> DeleteReport;
+    /// Moves the matched files to the trash of the desktop (freedesktop trash,
+    /// Finder, Recycle Bin) instead of removing them for good.
+    /// `Mode::DryRun` only reports what would be moved.
+    #[cfg(feature = "trash")]
+    fn delete_to_trash(&self, mode: Mode) -> DeleteReport;
+    /// Removes the directories under the root holding no files, deepest first, never the root.
+    /// Only directories the configured walk enters are considered: pruned, hidden, ignored
+    /// or too deep ones count as content and are left alone.
+    /// Reported in `pruned_dirs`, `Mode::DryRun` only reports what would be removed.
+    fn remove_empty_dirs(&self, mode: Mode) -> DeleteReport;
+    /// Renames every matched file on disk to its path after the hook chain.
+    /// Targets that already exist or are shared by several files are reported as failed.
+    fn apply_rename(&self, mode: Mode) -> ActionReport;
+    /// Mirrors the matched files under `dest_root` as links, keeping their path relative to the root.
+    fn link_into<P: AsRef<Path>>(&self, dest_root: P, kind: LinkKind) -> ActionReport;
+    /// Streams the matched files into a tar archive, paths relative to the root.
+    #[cfg(feature = "archive")]
+    fn write_tar<W: Write>(&self, writer: W, compression: TarCompression) -> io::Result<()>;
+    /// Writes the matched files into a zip archive under `prefix`.
+    /// `level` 0 stores files, 1..=9 deflates them.
+    #[cfg(feature = "archive")]
+    fn write_zip<W: Write + io::Seek>(&self, writer: W, level: u32, prefix: &str)
+        -> io::Result<()>;
+    /// Sets the unix permission bits (e.g. `0o755`) of every matched file.
+    #[cfg(unix)]
+    fn set_permissions(&self, perm: u32, mode: Mode) -> ActionReport;
+    /// Changes the owner and/or group of every matched file, `None` keeps the current one.
+    #[cfg(unix)]
+    fn set_owner(&self, uid: Option<u32>, gid: Option<u32>, mode: Mode) -> ActionReport;
+    /// Sets both the access and modification time of every matched file.
+    fn touch(&self, time: SystemTime, mode: Mode) -> ActionReport;
+    /// Writes the matched paths relative to the root for `rsync --files-from=-`,
+    /// NUL separated (`--from0`) when `nul` is set.
+    fn write_files_from<W: Write>(&self, writer: W, nul: bool) -> io::Result<()>;
+    /// Collects into a `PathSet`, resolving the paths first when `canonicalize` is set.
+    fn collect_set(&self, canonicalize: bool) -> PathSet;
+    /// Compares the root against `other_root` with the same filters, like `diff -rq`.
+    fn compare<P: AsRef<Path>>(&self, other_root: P, by: CompareBy) -> TreeDiff;
+    /// Total size of the matched files under every directory, root included, like a filtered `du`,
+    /// added up during the walk. Hooks and post-walk filters don't apply.
+    fn collect_usage(&self) -> Vec<(PathBuf, u64)>;
+    /// Count and total size of the matched files per extension, largest first, counted
+    /// during the walk. Hooks and post-walk filters don't apply.
+    fn summarize_by_extension(&self) -> Vec<ExtSummary>;
+    /// Line counts of the matched text files, skipping binaries and files over `max_bytes`.
+    /// Files that cannot be read are reported in `errors`.
+    fn collect_line_counts(&self, max_bytes: u64) -> ContentReport<u64>;
+    /// Keeps only files whose magic bytes match one of `mimes` (`"image/*"`, `"application/pdf"`).
+    /// Files that cannot be read and special files are left out.
+    fn with_mime_filter(self, mimes: &[&str]) -> CollectFilesConfigured;
+    /// Keeps only text files (no NUL byte, valid UTF-8 in the first 8 KiB),
+    /// files that cannot be read and special files are left out.
+    fn with_text_only(self) -> CollectFilesConfigured;
+    /// Keeps only files `with_text_only` would drop.
+    fn with_binary_only(self) -> CollectFilesConfigured;
+    /// Probable encoding of every matched text file, binaries are left out.
+    fn collect_encodings(&self) -> ContentReport<Encoding>;
+    /// Reads the matched files whole, skipping files over `limit_bytes`
+    /// and files that would take the total over `budget_bytes`.
+    fn collect_contents(&self, limit_bytes: u64, budget_bytes: u64) -> ContentReport<Vec<u8>>;
+    /// `collect_contents` keeping only valid UTF-8 files.
+    fn collect_strings(&self, limit_bytes: u64, budget_bytes: u64) -> ContentReport<String>;
+    /// Memory-maps every matched file read-only, files that cannot be mapped are
+    /// reported in `errors`. Nothing stops another process or thread from changing a
+    /// mapped file: reading a mapping after its file was truncated or rewritten is
+    /// undefined behavior, so keep the files unchanged for as long as the maps live.
+    #[cfg(feature = "mmap")]
+    fn collect_mmaps(&self) -> ContentReport<Mmap>;
+    /// At most the first `n` bytes of every matched file, skipping files that would
+    /// take the total held over `budget_bytes`. Files that cannot be read are reported in `errors`.
+    fn collect_previews(&self, n: usize, budget_bytes: u64) -> ContentReport<Vec<u8>>;
+    /// Lists the entries of .zip, .tar, .tar.gz and .tar.zst files as `bundle.zip!/docs/readme.md`,
+    /// filtered by the target regex. Only `collect` reports them, depth stops at the archive.
+    #[cfg(feature = "archive")]
+    fn with_descend_archives(self, yes: bool) -> CollectFilesConfigured;
+    fn with_strategy(self, strategy: Traversal) -> CollectFilesConfigured;
+    /// Same output order on every run: sorted directory entries, visited in order,
+    /// while directories are still read in parallel.
+    fn with_stable_order(self, yes: bool) -> CollectFilesConfigured;
+    /// A cursor at the root, to be driven by `collect_batch`.
+    fn cursor(&self) -> Cursor;
+    /// Reads up to `max_dirs` pending directories of `cursor` and returns what they matched.
+    /// Save `cursor` between calls to survive restarts, filters only see one batch at a time.
+    fn collect_batch(&self, cursor: &mut Cursor, max_dirs: usize) -> Vec<PathBuf>;
+    /// Finishes a saved cursor.
+    fn resume(&self, cursor: Cursor) -> Vec<PathBuf>;
+    /// Keeps a uniformly random subset of `n` matched files, drawn while walking so the
+    /// rest is never held. After `with_resolve_symlinks` or a dedupe it is drawn from
+    /// their result instead.
+    fn with_sample(self, n: usize) -> CollectFilesConfigured;
+    /// Keeps each matched file with probability `rate` (0.0..=1.0).
+    fn with_sample_rate(self, rate: f64) -> CollectFilesConfigured;
+    /// Like `with_hook`, but returning `None` drops the entry.
+    fn with_filter_map_hook(
+        self,
+        hook_fn: fn(PathBuf) -> Option<PathBuf>,
+    ) -> CollectFilesConfigured;
+    /// Fallible hook. `collect` panics on its errors, `try_collect` reports them.
+    fn with_try_hook(self, hook_fn: fn(PathBuf) -> io::Result<PathBuf>) -> CollectFilesConfigured;
+    /// `collect`, with unreadable directories and the errors of `with_try_hook`
+    /// reported instead of panicking. An error handler still takes precedence.
+    fn try_collect(&self) -> CollectReport;
+    /// Asks `hook_fn` about every directory below the root before it is entered.
+    fn with_dir_hook(self, hook_fn: fn(&Path) -> DirAction) -> CollectFilesConfigured;
+    /// Decides what happens when a directory cannot be read, knowing which one it was.
+    /// Takes over from `with_unwrap_or_else`.
+    fn with_error_handler(
+        self,
+        handler: fn(&Path, io::Error) -> ErrorAction,
+    ) -> CollectFilesConfigured;
+    /// Tries a directory read or metadata call failing with EINTR, EAGAIN or a timeout
+    /// up to `n` more times, pausing `backoff` and then twice as long each time,
+    /// before the error handler sees the failure.
+    fn with_retries(self, n: usize, backoff: Duration) -> CollectFilesConfigured;
+    /// `collect`, appending to `target` so a buffer can be reused across scans. The walk
+    /// writes straight into it unless post-walk filters, sampling or extra roots are set.
+    fn collect_into(&self, target: &mut Vec<PathBuf>);
+    /// `collect` straight into any parallel-collectable container, e.g. a `HashSet`.
+    fn collect_as<C: FromParallelIterator<PathBuf>>(&self) -> C;
+    /// `collect` packed into a `CompactPaths`, for scans of millions of files.
+    fn collect_compact(&self) -> CompactPaths;
+    /// `collect` as (parent, file name) pairs sharing one `Arc` per directory.
+    fn collect_interned(&self) -> InternedPaths;
+    /// Predicts the number and total size of matched files from a few random walks
+    /// down the tree, cheap next to a full `collect`. The walk settings (pruning, hidden
+    /// files, ignore files) and limits apply; hooks and post-walk filters do not, the
+    /// hooks only ever run on the real walk.
+    fn estimate(&self) -> Estimate;
+    /// Sizes the result of `collect` for `files` paths up front, e.g. from
+    /// `estimate().capacity()`, sparing the reallocations of a large scan.
+    fn with_capacity_hint(self, files: usize) -> CollectFilesConfigured;
+    /// Keeps at most `n` directories open at once, to stay under `ulimit -n` on wide trees.
+    /// With `with_openat`, at most `n` being opened and listed at once.
+    fn with_max_open_dirs(self, n: usize) -> CollectFilesConfigured;
+    /// Paces the read_dir and metadata calls of the walk to `ops_per_sec` across all workers,
+    /// so a background scan leaves the disk to interactive work.
+    fn with_io_throttle(self, ops_per_sec: u32) -> CollectFilesConfigured;
+    /// Reads directories on spinning disks one at a time and on network mounts
+    /// two at a time, SSD subtrees keep full parallelism. (Linux)
+    fn with_adaptive_parallelism(self, yes: bool) -> CollectFilesConfigured;
+    /// Walks directories whose path matches `regex` with `depth` levels below them
+    /// instead of the global depth, `usize::MAX` for no limit. The last matching override wins,
+    /// `with_dir_hook` is asked first.
+    fn with_depth_override(self, regex: &str, depth: usize) -> CollectFilesConfigured;
+    /// Leaves out every directory for which `prune(dir, level)` returns true,
+    /// `level` being 1 for the root's own subdirectories. Asked before `with_dir_hook`.
+    fn with_depth_prune(self, prune: fn(&Path, usize) -> bool) -> CollectFilesConfigured;
+    /// Streams the matched files of every directory as one sorted batch as soon as
+    /// the directory is read, parents before their subdirectories. Memory stays bounded
+    /// by a slow consumer, filters only see one directory at a time.
+    fn stream_by_dir(&self) -> DirBatches;
+    /// Walks without collecting anything, pushing every matched file with its metadata
+    /// to `visitor`. Only the walk settings apply, post-walk filters and hooks don't.
+    fn collect_with<V: FileVisitor>(&self, visitor: V) -> V;
+    /// Folds every matched path (after the hooks) into per-thread accumulators during the walk
+    /// and merges them with `reduce_op`, without building the path list.
+    /// Post-walk filters don't apply.
+    fn fold<A, ID, F, R>(&self, identity: ID, fold_op: F, reduce_op: R) -> A
+    where
+        A: Send,
+        ID: Fn() -> A + Sync + Send,
+        F: Fn(A, PathBuf) -> A + Sync + Send,
+        R: Fn(A, A) -> A + Sync + Send;
+    /// Maps every matched path on the walking threads, e.g. to hash it, and merges the
+    /// results with `reduce_fn` without collecting them. `None` when nothing matched.
+    fn map_reduce<U, M, R>(&self, map_fn: M, reduce_fn: R) -> Option<U>
+    where
+        U: Send,
+        M: Fn(PathBuf) -> U + Sync + Send,
+        R: Fn(U, U) -> U + Sync + Send;
+    /// Describes the effective configuration and, from a short sampling walk,
+    /// the first directories left out and why.
+    fn explain(&self) -> Explain;
+    /// Leaves out dotfiles and doesn't descend into dot-directories.
+    fn with_skip_hidden(self, yes: bool) -> CollectFilesConfigured;
+    /// Doesn't descend into directories with one of these names, e.g. `node_modules`.
+    fn with_prune_dirs(self, names: &[&str]) -> CollectFilesConfigured;
+    /// Honors `.ignore` files (gitignore syntax, as read by ripgrep and fd) in the
+    /// root and every directory below it. Deeper files override shallower ones.
+    fn with_ignore_files(self, yes: bool) -> CollectFilesConfigured;
+    /// Reports symlinked files by their resolved target instead of the link.
+    /// A target reached through several links is reported once; broken links stay as they are.
+    fn with_resolve_symlinks(self, yes: bool) -> CollectFilesConfigured;
+    /// The matched files with their metadata, before the hooks, see `with_dereference`.
+    /// Under `with_resolve_symlinks` the link each target came from is kept in `FileEntry::symlink`.
+    fn collect_entries(&self) -> Vec<FileEntry>;
+    /// Whether `collect_entries` reads the metadata of symlink targets (`ls -lL`)
+    /// instead of the links themselves (`ls -l`, the default).
+    fn with_dereference(self, yes: bool) -> CollectFilesConfigured;
+    /// Keeps only files with at least `n` hard links, e.g. `2` for files shared
+    /// between snapshot directories. (Unix; elsewhere every file has one link)
+    fn with_min_nlink(self, n: u64) -> CollectFilesConfigured;
+    /// Keeps only sparse files, those allocating fewer bytes than their length. (Unix)
+    fn with_sparse_only(self) -> CollectFilesConfigured;
+    /// Leaves out the files macOS scatters around: `.DS_Store`, AppleDouble `._*`,
+    /// and doesn't descend into `.Spotlight-V100`, `.Trashes` or `.fseventsd`.
+    fn with_skip_macos_metadata(self) -> CollectFilesConfigured;
+    /// Runs the configured collection on every mounted volume (see `volumes`) in
+    /// parallel instead of the root, merged into one sorted list.
+    /// Volumes mounted inside another one are walked as part of it.
+    fn collect_volumes(&self) -> Vec<PathBuf>;
+    /// Matches the target regex and depth overrides against paths written with `/`
+    /// even where they are read with `\`, so one pattern works for Windows and UNC
+    /// (`\\server\share`) roots. The returned paths keep their native separators.
+    fn with_normalize_separators(self, yes: bool) -> CollectFilesConfigured;
+    /// Reports a file reached through paths differing only in letter case once, as
+    /// happens on case-insensitive filesystems with symlinks or junctions.
+    /// Both spellings must be the same file: the same inode on Unix, the same
+    /// canonical path elsewhere, both kept when that cannot be told.
+    fn with_dedupe_case_insensitive(self, yes: bool) -> CollectFilesConfigured;
+    /// Skips files and directories whose full path is longer than `n` bytes, for
+    /// archives, databases or systems with stricter limits. `try_collect` reports them.
+    fn with_max_path_len(self, n: usize) -> CollectFilesConfigured;
+    /// Neither reports nor enters symlinks resolving outside the root, nor broken
+    /// ones, for scanning trees supplied by someone else.
+    fn with_confine_to_root(self, yes: bool) -> CollectFilesConfigured;
+    /// Walks by directory descriptors, `fstatat` / `O_NOFOLLOW` with every directory
+    /// checked to be the one its parent listed, so a tree changed during the walk
+    /// cannot redirect it through a swapped-in symlink. Symlinks below the root are reported as they are and
+    /// never entered; the root itself may be one. Directories are opened relative to their
+    /// parent, which stays open meanwhile: `with_max_open_dirs` does not count these held
+    /// descriptors, one per level of depth and worker.
+    /// Covers `collect` and what builds on it. (Unix; elsewhere the usual walk)
+    fn with_openat(self, yes: bool) -> CollectFilesConfigured;
+    /// Reports paths under `old_root` under `new_prefix` instead, e.g. a snapshot
+    /// mounted at `/mnt/snap` as the live `/srv`. Runs as a link of the hook chain,
+    /// paths outside `old_root` pass unchanged.
+    fn with_rebase<P: AsRef<Path>, Q: AsRef<Path>>(
+        self,
+        old_root: P,
+        new_prefix: Q,
+    ) -> CollectFilesConfigured;
+    /// Quickly probes whether the root can be listed and, with `children`, whether
+    /// its subdirectories can be listed and its regular files opened, so a long scan
+    /// can be refused up front with a useful message. Special files are not opened.
+    fn check_access(&self, children: bool) -> AccessReport;
+    /// Stores `collect_entries` into the `entries` table (path, size, mtime, type, hash)
+    /// of the SQLite database at `db`, indexed on size, mtime and hash, to be queried
+    /// with SQL afterwards. The table is replaced; `hash` fills the hash column.
+    #[cfg(feature = "sqlite")]
+    fn write_sqlite<P: AsRef<Path>>(&self, db: P, hash: Option<HashAlgo>) -> io::Result<()>;
+    /// Writes `collect_entries` as a Parquet table (path, size, mtime as a UTC
+    /// millisecond timestamp, type, hash) to `out`, for Spark, DuckDB and friends.
+    /// `hash` fills the hash column.
+    #[cfg(feature = "parquet")]
+    fn write_parquet<P: AsRef<Path>>(&self, out: P, hash: Option<HashAlgo>) -> io::Result<()>;
+    /// Prints every matched path to stdout as soon as its directory is read, like
+    /// `fd`, instead of after the whole walk. Output is buffered and flushed whenever
+    /// the walk has nothing new; a closed pipe (`| head`) ends it without an error.
+    fn print_streaming(&self) -> io::Result<()>;
+    /// Counts files and bytes per size range during the walk, `buckets` being the
+    /// boundaries: `&[1 << 10, 100 << 10, 10 << 20]` gives <1KiB, 1KiB-100KiB,
+    /// 100KiB-10MiB and >=10MiB. Hooks and post-walk filters don't apply.
+    fn histogram_by_size(&self, buckets: &[u64]) -> Vec<Bucket<u64>>;
+    /// Counts files and bytes per time since last modification during the walk,
+    /// `buckets` being the boundaries, e.g. 30 and 365 days for "this month",
+    /// "this year" and "untouched for over a year". Files modified
+    /// in the future count as age zero. Hooks and post-walk filters don't apply.
+    fn histogram_by_age(&self, buckets: &[Duration]) -> Vec<Bucket<Duration>>;
+    /// Also walks `root`, sharing the thread pool and the results with the main root.
+    /// `overrides` adjusts a copy of this configuration for that root alone,
+    /// e.g. `|c| c.with_depth(2)`; hooks stay the shared ones. Actions relative to
+    /// the root (`copy_to`, manifests, ..) keep using the main root.
+    fn with_root<P: AsRef<Path>>(
+        self,
+        root: P,
+        overrides: fn(CollectFilesConfigured) -> CollectFilesConfigured,
+    ) -> CollectFilesConfigured;
+    /// Walks on a pool of its own with `n` threads instead of the shared rayon pool,
+    /// keeping the shared one where no threads can be spawned. Panics if the pool
+    /// cannot be built.
+    fn with_threads(self, n: usize) -> CollectFilesConfigured;
+    /// Applies the settings found in the environment on top of this configuration:
+    /// `COLLECTFILES_THREADS` (`with_threads`), `COLLECTFILES_SKIP_HIDDEN` (`1`/`0`,
+    /// `with_skip_hidden`) and `COLLECTFILES_EXCLUDE` (comma separated, `with_prune_dirs`).
+    /// Unset variables change nothing, malformed ones panic.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_env(self) -> CollectFilesConfigured;
+    /// Replaces every path `regex` matches with `template` expanded from its captures
+    /// (`$1`, `${name}`), e.g. `(r"(\w+)/(\w+)\.log$", "archive/$1/$2.bak")`.
+    /// Relative results are joined to the root. Runs as a link of the hook chain, so
+    /// `apply_rename` renames to it; paths that don't match pass unchanged.
+    fn with_rename_template(self, regex: &str, template: &str) -> CollectFilesConfigured;
+    /// Replaces every path with `template` filled from its tokens, e.g.
+    /// `"{parent}/{stem}_backup.{ext}"`: `{parent}`, `{name}`, `{stem}`, `{ext}` (empty
+    /// without one), `{depth}` (1 for the root's own entries) and `{mtime}` (`YYYY-MM-DD`, UTC).
+    /// Relative results are joined to the root. Runs as a link of the hook chain;
+    /// panics on an unknown token.
+    fn with_template(self, template: &str) -> CollectFilesConfigured;
+    /// (matched file, its path after the hook chain) pairs, without touching anything,
+    /// to show what `apply_rename` or a copy would do. Files dropped by a filter-map hook
+    /// are left out; panics on the first failure of `with_try_hook`.
+    fn preview_hook(&self) -> Vec<(PathBuf, PathBuf)>;
+    /// Rewrites every matched file with what `transform` returns for its contents,
+    /// through a temporary file renamed over the original. Files left unchanged are
+    /// reported as skipped; `Mode::DryRun` still runs `transform` but writes nothing.
+    fn transform_contents<F>(&self, transform: F, mode: Mode) -> ActionReport
+    where
+        F: Fn(&[u8]) -> io::Result<Vec<u8>> + Sync;
+    /// Before `delete`, `apply_rename` and `transform_contents` touch a file, copies it
+    /// under `backup_dir/<YYYY-MM-DD_HHMMSS>/` (UTC, one tree per action) at its path
+    /// relative to the root. A file that cannot be saved is reported as failed and left alone.
+    fn with_backup_dir<P: AsRef<Path>>(self, backup_dir: P) -> CollectFilesConfigured;
+    /// How actions and content passes (hashing, reading, content filters) treat files
+    /// another process keeps open without sharing them, `LockPolicy::Error` by default.
+    /// Actions list the files finally left alone in their report's `locked`. (Windows)
+    fn with_lock_policy(self, policy: LockPolicy) -> CollectFilesConfigured;
+    /// Collects from a remote host instead, the root being a path there, over an
+    /// authenticated SFTP session: depth rules, target regex, hidden and pruned names,
+    /// directory hooks and hooks apply; ignore files and post-walk filters, which read
+    /// local files, don't. Symlinks are reported and never entered.
+    #[cfg(feature = "sftp")]
+    fn collect_sftp(&self, sftp: &ssh2::Sftp) -> CollectReport;
+    /// `collect`, kept in a zstd-compressed cache file between runs: loaded from `cache`
+    /// when it holds a valid cache of this configuration, otherwise collected and written
+    /// there. A cache from another configuration (root, filters, hooks, ..), version or
+    /// platform, or failing its checksum, is rebuilt; hooks count by their place in the
+    /// binary, so a rebuild of the program rebuilds the cache too.
+    /// Nothing tells whether the tree changed since, remove the file to refresh it.
+    #[cfg(feature = "cache")]
+    fn collect_cached<P: AsRef<Path>>(&self, cache: P) -> io::Result<Vec<PathBuf>>;
+    /// Hands the matched files (after the hooks) to `sink` in chunks of `batch_size`,
+    /// the last one shorter, on the walking threads while the walk runs, without building
+    /// the path list. Like `fold`, post-walk filters don't apply. A terminal rather than a
+    /// `with_` setting: the other terminals sort, dedupe or filter their list after the
+    /// walk, while the chunks leave during it.
+    fn for_each_batch<F>(&self, batch_size: usize, sink: F)
+    where
+        F: Fn(Vec<PathBuf>) + Sync;
+    /// Directory batches `stream_by_dir` buffers ahead of the consumer, 64 by default.
+    /// Once that many wait, the walk pauses until the consumer catches up; `0` hands
+    /// every batch over directly. (unbounded where no threads can be spawned)
+    fn with_channel_capacity(self, n: usize) -> CollectFilesConfigured;
+    /// Reads directories in chunks of `chunk` entries, matching each chunk on another
+    /// worker (and entering its subdirectories) while the rest is still read, so one
+    /// directory of millions of files keeps every core busy. Under `with_stable_order`
+    /// the listing is read whole first; the breadth-first walk and `stream_by_dir`
+    /// match chunks of larger directories in parallel.
+    fn with_split_large_dirs(self, chunk: usize) -> CollectFilesConfigured;
 }
 use private::*;
 pub mod private {
     use super::*;
+    type RootOverrides = fn(CollectFilesConfigured) -> CollectFilesConfigured;
     #[derive(Debug, Default, Clone)]
     pub struct CollectFilesConfigured {
         root_dir: PathBuf,
         depth: Option<usize>,
-        hook_fn: Option<fn(PathBuf) -> PathBuf>,
+        hooks: Vec<hook::Hook>,
         target_regex: Option<Regex>,
         unwrap_or_else: Option<fn(io::Error) -> PathBuf>,
+        dedupe_hardlinks: bool,
+        mime_filter: Option<Vec<String>>,
+        content_class: Option<content::ContentClass>,
+        descend_archives: bool,
+        strategy: Traversal,
+        stable_order: bool,
+        sample: Option<sample::Sample>,
+        dir_hook: Option<fn(&Path) -> DirAction>,
+        error_handler: Option<fn(&Path, io::Error) -> ErrorAction>,
+        retries: usize,
+        backoff: Duration,
+        open_dirs: Option<Arc<limit::Semaphore>>,
+        throttle: Option<Arc<limit::Pacer>>,
+        devices: Option<Arc<device::Devices>>,
+        /// `with_max_depth(0)`, not even the entries of the root are reported.
+        depth_zero: bool,
+        depth_overrides: Vec<(Regex, usize)>,
+        depth_prune: Option<fn(&Path, usize) -> bool>,
+        skip_hidden: bool,
+        prune_dirs: Vec<String>,
+        ignore_files: bool,
+        resolve_symlinks: bool,
+        dereference: bool,
+        min_nlink: Option<u64>,
+        sparse_only: bool,
+        skip_macos_files: bool,
+        /// mount points `collect_volumes` doesn't descend into.
+        skip_mounts: Vec<PathBuf>,
+        normalize_separators: bool,
+        dedupe_case: bool,
+        max_path_len: Option<usize>,
+        confine_to_root: bool,
+        openat: bool,
+        /// extra roots of `with_root` with their overrides.
+        roots: Vec<(PathBuf, RootOverrides)>,
+        /// `with_threads`, the shared rayon pool when `None`.
+        pool: Option<Arc<rayon::ThreadPool>>,
+        backup_dir: Option<PathBuf>,
+        lock_policy: LockPolicy,
+        channel_capacity: Option<usize>,
+        split_dirs: Option<usize>,
+        /// `with_capacity_hint`, the paths `collect` allocates for up front.
+        capacity: usize,
     }
     impl CollectFilesConfigured {
         pub fn new(root_dir: PathBuf) -> Self {
@@ -75,7 +618,10 @@ pub mod private {
         }
         #[inline]
         fn as_hook(&self) -> Option<fn(PathBuf) -> PathBuf> {
-            self.hook_fn
+            self.hooks.iter().rev().find_map(|h| match h {
+                hook::Hook::Map(f) => Some(*f),
+                _ => None,
+            })
         }
         #[inline]
         fn as_depth(&self) -> Option<usize> {
@@ -83,12 +629,19 @@ pub mod private {
         }
         #[inline]
         fn with_hook(mut self, hook_fn: fn(PathBuf) -> PathBuf) -> Self {
-            self.hook_fn = Some(hook_fn);
+            self.hooks.push(hook::Hook::Map(hook_fn));
+            self
+        }
+        #[inline]
+        fn with_max_depth(mut self, n: usize) -> CollectFilesConfigured {
+            self.depth = Some(n.saturating_sub(1));
+            self.depth_zero = n == 0;
             self
         }
         #[inline]
         fn with_depth(mut self, level: usize) -> Self {
             self.depth = Some(level);
+            self.depth_zero = false;
             self
         }
         #[inline]
@@ -104,154 +657,2412 @@ pub mod private {
             self
         }
         #[inline]
+        fn with_dedupe_hardlinks(mut self, yes: bool) -> CollectFilesConfigured {
+            self.dedupe_hardlinks = yes;
+            self
+        }
+        #[inline]
         fn collect(&self) -> Vec<PathBuf> {
-            collect_files(
-                self.root_dir.clone(),
-                self.depth,
-                self.hook_fn,
-                self.target_regex.clone(),
-                self.unwrap_or_else,
+            self.collect_as()
+        }
+        #[inline]
+        fn collect_hashed(&self, algo: HashAlgo) -> ContentReport<Hash> {
+            hash::hash_files(self.collect_matched(), algo, self.lock_policy)
+        }
+        #[inline]
+        fn collect_duplicates(&self) -> Vec<Vec<PathBuf>> {
+            self.collect_duplicates_by(DupeStage::Full)
+        }
+        #[inline]
+        fn collect_duplicates_by(&self, stage: DupeStage) -> Vec<Vec<PathBuf>> {
+            dupes::find_duplicates(self.collect_matched(), stage)
+        }
+        #[inline]
+        fn collect_name_collisions(&self) -> Vec<Vec<PathBuf>> {
+            dupes::find_name_collisions(self.collect_matched())
+        }
+        #[inline]
+        fn write_manifest<W: Write>(&self, writer: W, algo: HashAlgo) -> io::Result<()> {
+            hash::write_manifest(
+                self.collect_matched(),
+                &self.root_dir,
+                algo,
+                self.lock_policy,
+                writer,
             )
         }
-    }
-}
-
-/// CollectFiles(`entry_dir`)
-///
-/// ## Example
-/// ```ignore
-/// use collectfiles::*;
-///
-/// let vec = CollectFiles("/Users/hwakyeom/programs/")
-///         .with_depth(1)
-///         .with_target_regex(".md$")
-///         .with_hook(|path| path.with_extension("mutated"))
-///         .with_unwrap_or_else(|e| {
-///             if e.kind() == io::ErrorKind::NotFound {
-///                 PathBuf::from("/Users/other/")
-///             } else {
-///                panic!("{:?}", e)
-///             }
-///         })
-///         .collect();
-///
-/// println!("{:#?}", vec);
-/// ```
-#[derive(Debug)]
-pub struct CollectFiles<T>(pub T)
-where
-    T: AsRef<Path> + Clone + Send + Sync;
-
-impl<T> CollectFiles<T>
-where
-    T: AsRef<Path> + Clone + Send + Sync,
-{
-    fn clone(&self) -> CollectFilesConfigured {
-        CollectFilesConfigured::new(self.0.as_ref().to_path_buf())
-    }
-}
-
-impl<T> CollectFilesPrelude for CollectFiles<T>
-where
-    T: AsRef<Path> + Clone + Send + Sync,
-{
-    #[inline]
-    fn as_target_regex(&self) -> Option<&str> {
-        None
-    }
-    #[inline]
-    fn as_root_dir(&self) -> &Path {
-        self.0.as_ref()
-    }
-    #[inline]
-    fn as_hook(&self) -> Option<fn(PathBuf) -> PathBuf> {
-        None
-    }
-    #[inline]
-    fn as_depth(&self) -> Option<usize> {
-        None
-    }
-    #[inline]
-    fn with_hook(self, hook_fn: fn(PathBuf) -> PathBuf) -> CollectFilesConfigured {
-        self.clone().with_hook(hook_fn)
-    }
-    #[inline]
-    fn with_depth(self, level: usize) -> CollectFilesConfigured {
-        self.clone().with_depth(level)
-    }
-    #[inline]
-    fn with_target_regex(self, regex: &str) -> CollectFilesConfigured {
-        self.clone().with_target_regex(regex)
-    }
-    #[inline]
-    fn with_unwrap_or_else(self, f: fn(io::Error) -> PathBuf) -> CollectFilesConfigured {
-        self.clone().with_unwrap_or_else(f)
-    }
-    #[inline]
-    fn collect(&self) -> Vec<PathBuf> {
-        collect_files(self.0.as_ref().to_path_buf(), None, None, None, None)
-    }
-}
-#[inline]
-fn collect_files(
-    dir_path: PathBuf,
-    depth: Option<usize>,
-    hook_fn: Option<fn(PathBuf) -> PathBuf>,
-    target_regex: Option<Regex>,
-    unwrap_or_else: Option<fn(io::Error) -> PathBuf>,
-) -> Vec<PathBuf> {
-    let paths = if let Some(f) = unwrap_or_else {
-        fs::read_dir(dir_path)
-            .unwrap_or_else(|e| fs::read_dir(f(e)).unwrap())
-            .par_bridge()
-    } else {
-        fs::read_dir(dir_path).unwrap().par_bridge()
-    };
-
-    paths
-        .flat_map(|p| {
-            let path = if let Some(f) = unwrap_or_else {
-                match p {
-                    Ok(v) => v.path(),
-                    Err(e) => f(e),
-                }
-            } else {
-                p.unwrap().path()
+        #[inline]
+        fn verify_manifest<R: BufRead>(
+            &self,
+            reader: R,
+            algo: HashAlgo,
+        ) -> io::Result<VerifyReport> {
+            hash::verify_manifest(self.collect_matched(), &self.root_dir, algo, reader)
+        }
+        #[inline]
+        fn copy_to<P: AsRef<Path>>(&self, dest_root: P, collision: Collision) -> ActionReport {
+            let copy =
+                |paths| actions::copy_to(paths, &self.root_dir, dest_root.as_ref(), collision);
+            self.lock_policy.settle(copy(self.collect_matched()), copy)
+        }
+        #[inline]
+        fn move_to<P: AsRef<Path>>(
+            &self,
+            dest_root: P,
+            collision: Collision,
+            mode: Mode,
+        ) -> ActionReport {
+            let move_to = |paths| {
+                actions::move_to(paths, &self.root_dir, dest_root.as_ref(), collision, mode)
             };
-            if path.is_dir() {
-                match depth {
-                    Some(dep) if dep > 0 => collect_files(
-                        path,
-                        Some(dep - 1),
-                        hook_fn,
-                        target_regex.clone(),
-                        unwrap_or_else,
-                    ),
-                    Some(_) => vec![PathBuf::default()],
-                    None => {
-                        collect_files(path, depth, hook_fn, target_regex.clone(), unwrap_or_else)
-                    }
-                }
+            self.lock_policy
+                .settle(move_to(self.collect_matched()), move_to)
+        }
+        #[inline]
+        fn delete(&self, mode: Mode, prune_empty_dirs: bool) -> DeleteReport {
+            let backup = self.backup();
+            let delete = |paths| {
+                actions::delete(
+                    paths,
+                    &self.root_dir,
+                    mode,
+                    prune_empty_dirs,
+                    backup.as_ref(),
+                )
+            };
+            self.lock_policy
+                .settle(delete(self.collect_matched()), delete)
+        }
+        #[cfg(feature = "trash")]
+        #[inline]
+        fn delete_to_trash(&self, mode: Mode) -> DeleteReport {
+            let trash = |paths| actions::delete_to_trash(paths, mode);
+            self.lock_policy
+                .settle(trash(self.collect_matched()), trash)
+        }
+        #[inline]
+        fn remove_broken_symlinks(&self, mode: Mode) -> DeleteReport {
+            actions::remove_broken_symlinks(self.collect_matched(), mode)
+        }
+        #[inline]
+        fn remove_empty_dirs(&self, mode: Mode) -> DeleteReport {
+            if self.depth_zero {
+                return DeleteReport::default();
+            }
+            let cfg = WalkConfig {
+                errors: Some(Mutex::new(Vec::new())),
+                ..self.walk_config()
+            };
+            actions::remove_empty_dirs(&self.root_dir, self.depth, &cfg, mode)
+        }
+        #[inline]
+        fn apply_rename(&self, mode: Mode) -> ActionReport {
+            let backup = self.backup();
+            let rename = |paths| actions::apply_rename(paths, &self.hooks, mode, backup.as_ref());
+            self.lock_policy
+                .settle(rename(self.collect_matched()), rename)
+        }
+        #[inline]
+        fn link_into<P: AsRef<Path>>(&self, dest_root: P, kind: LinkKind) -> ActionReport {
+            actions::link_into(
+                self.collect_matched(),
+                &self.root_dir,
+                dest_root.as_ref(),
+                kind,
+            )
+        }
+        #[cfg(feature = "archive")]
+        #[inline]
+        fn write_tar<W: Write>(&self, writer: W, compression: TarCompression) -> io::Result<()> {
+            archive::write_tar(self.collect_matched(), &self.root_dir, writer, compression)
+        }
+        #[cfg(feature = "archive")]
+        #[inline]
+        fn write_zip<W: Write + io::Seek>(
+            &self,
+            writer: W,
+            level: u32,
+            prefix: &str,
+        ) -> io::Result<()> {
+            archive::write_zip(
+                self.collect_matched(),
+                &self.root_dir,
+                writer,
+                level,
+                prefix,
+            )
+        }
+        #[cfg(unix)]
+        #[inline]
+        fn set_permissions(&self, perm: u32, mode: Mode) -> ActionReport {
+            let chmod = |paths| actions::set_permissions(paths, perm, mode);
+            self.lock_policy
+                .settle(chmod(self.collect_matched()), chmod)
+        }
+        #[cfg(unix)]
+        #[inline]
+        fn set_owner(&self, uid: Option<u32>, gid: Option<u32>, mode: Mode) -> ActionReport {
+            let chown = |paths| actions::set_owner(paths, uid, gid, mode);
+            self.lock_policy
+                .settle(chown(self.collect_matched()), chown)
+        }
+        #[inline]
+        fn touch(&self, time: SystemTime, mode: Mode) -> ActionReport {
+            let touch = |paths| actions::touch(paths, time, mode);
+            self.lock_policy
+                .settle(touch(self.collect_matched()), touch)
+        }
+        #[inline]
+        fn write_files_from<W: Write>(&self, writer: W, nul: bool) -> io::Result<()> {
+            export::write_files_from(self.collect_matched(), &self.root_dir, writer, nul)
+        }
+        #[inline]
+        fn collect_set(&self, canonicalize: bool) -> PathSet {
+            if canonicalize {
+                PathSet::canonicalized(self.collect())
             } else {
-                match &target_regex {
-                    Some(r)
-                        if r.is_match(path.to_str().unwrap_or_else(|| {
-                            panic!("* not a valid unicode extension: {}", path.display())
-                        })) =>
-                    {
-                        if let Some(hook) = hook_fn {
-                            vec![hook(path)]
-                        } else {
-                            vec![path]
+                self.collect().into()
+            }
+        }
+        #[inline]
+        fn compare<P: AsRef<Path>>(&self, other_root: P, by: CompareBy) -> TreeDiff {
+            let mut other = self.clone();
+            other.root_dir = other_root.as_ref().to_path_buf();
+            compare::compare(
+                self.collect_matched(),
+                &self.root_dir,
+                other.collect_matched(),
+                &other.root_dir,
+                by,
+            )
+        }
+        fn collect_usage(&self) -> Vec<(PathBuf, u64)> {
+            let unhooked = CollectFilesConfigured {
+                hooks: Vec::new(),
+                ..self.clone()
+            };
+            let totals = unhooked.fold(
+                report::Usage::new,
+                |acc, path| report::add_usage(acc, &path, &self.root_dir),
+                report::merge_usage,
+            );
+            report::usage(totals)
+        }
+        fn summarize_by_extension(&self) -> Vec<ExtSummary> {
+            let unhooked = CollectFilesConfigured {
+                hooks: Vec::new(),
+                ..self.clone()
+            };
+            let totals = unhooked.fold(
+                report::ExtTotals::new,
+                |acc, path| report::add_extension(acc, &path),
+                report::merge_extensions,
+            );
+            report::by_extension(totals)
+        }
+        #[inline]
+        fn collect_line_counts(&self, max_bytes: u64) -> ContentReport<u64> {
+            content::line_counts(self.collect_matched(), max_bytes, self.lock_policy)
+        }
+        #[inline]
+        fn with_mime_filter(mut self, mimes: &[&str]) -> CollectFilesConfigured {
+            self.mime_filter = Some(mimes.iter().map(|m| m.to_ascii_lowercase()).collect());
+            self
+        }
+        #[inline]
+        fn with_text_only(mut self) -> CollectFilesConfigured {
+            self.content_class = Some(content::ContentClass::Text);
+            self
+        }
+        #[inline]
+        fn with_binary_only(mut self) -> CollectFilesConfigured {
+            self.content_class = Some(content::ContentClass::Binary);
+            self
+        }
+        #[inline]
+        fn collect_encodings(&self) -> ContentReport<Encoding> {
+            content::encodings(self.collect_matched(), self.lock_policy)
+        }
+        #[inline]
+        fn collect_contents(&self, limit_bytes: u64, budget_bytes: u64) -> ContentReport<Vec<u8>> {
+            content::contents(
+                self.collect_matched(),
+                limit_bytes,
+                budget_bytes,
+                self.lock_policy,
+            )
+        }
+        #[inline]
+        fn collect_strings(&self, limit_bytes: u64, budget_bytes: u64) -> ContentReport<String> {
+            let contents = self.collect_contents(limit_bytes, budget_bytes);
+            ContentReport {
+                files: contents
+                    .files
+                    .into_par_iter()
+                    .filter_map(|(path, buf)| String::from_utf8(buf).ok().map(|s| (path, s)))
+                    .collect(),
+                errors: contents.errors,
+            }
+        }
+        #[cfg(feature = "mmap")]
+        #[inline]
+        fn collect_mmaps(&self) -> ContentReport<Mmap> {
+            content::mmaps(self.collect_matched(), self.lock_policy)
+        }
+        #[inline]
+        fn collect_previews(&self, n: usize, budget_bytes: u64) -> ContentReport<Vec<u8>> {
+            content::previews(self.collect_matched(), n, budget_bytes, self.lock_policy)
+        }
+        #[cfg(feature = "archive")]
+        #[inline]
+        fn with_descend_archives(mut self, yes: bool) -> CollectFilesConfigured {
+            self.descend_archives = yes;
+            self
+        }
+        #[inline]
+        fn with_strategy(mut self, strategy: Traversal) -> CollectFilesConfigured {
+            self.strategy = strategy;
+            self
+        }
+        #[inline]
+        fn with_stable_order(mut self, yes: bool) -> CollectFilesConfigured {
+            self.stable_order = yes;
+            self
+        }
+        #[inline]
+        fn cursor(&self) -> Cursor {
+            if self.depth_zero {
+                return Cursor::default();
+            }
+            Cursor::new(self.root_dir.clone(), self.depth)
+        }
+        fn collect_batch(&self, cursor: &mut Cursor, max_dirs: usize) -> Vec<PathBuf> {
+            let take = max_dirs.max(1).min(cursor.pending.len());
+            let batch: Vec<_> = cursor.pending.drain(..take).collect();
+            let cfg = self.walk_config();
+            let listed: Vec<(Matched, Vec<cursor::Pending>)> = batch
+                .into_par_iter()
+                .map(|(dir, depth)| read_one(dir, depth, &cfg))
+                .collect();
+            let (mut files, mut entries) = (Vec::new(), Vec::new());
+            for ((matched, more), dirs) in listed {
+                files.extend(matched);
+                entries.extend(more);
+                cursor.pending.extend(dirs);
+            }
+            let mut paths = self.filtered(files);
+            paths.extend(entries);
+            self.hooked(paths)
+        }
+        fn resume(&self, mut cursor: Cursor) -> Vec<PathBuf> {
+            let mut found = Vec::new();
+            while !cursor.is_done() {
+                let max_dirs = cursor.pending.len();
+                found.extend(self.collect_batch(&mut cursor, max_dirs));
+            }
+            found
+        }
+        #[inline]
+        fn with_sample(mut self, n: usize) -> CollectFilesConfigured {
+            self.sample = Some(sample::Sample::Count(n));
+            self
+        }
+        #[inline]
+        fn with_sample_rate(mut self, rate: f64) -> CollectFilesConfigured {
+            self.sample = Some(sample::Sample::Rate(rate));
+            self
+        }
+        #[inline]
+        fn with_filter_map_hook(
+            mut self,
+            hook_fn: fn(PathBuf) -> Option<PathBuf>,
+        ) -> CollectFilesConfigured {
+            self.hooks.push(hook::Hook::FilterMap(hook_fn));
+            self
+        }
+        #[inline]
+        fn with_try_hook(
+            mut self,
+            hook_fn: fn(PathBuf) -> io::Result<PathBuf>,
+        ) -> CollectFilesConfigured {
+            self.hooks.push(hook::Hook::Try(hook_fn));
+            self
+        }
+        #[inline]
+        fn try_collect(&self) -> CollectReport {
+            let cfg = Arc::new(WalkConfig {
+                errors: Some(Mutex::new(Vec::new())),
+                ..self.walk_config()
+            });
+            let walked = self.walk(&cfg, 0);
+            let mut paths = self.filtered(walked);
+            paths.extend(cfg.take_entries());
+            let (paths, mut errors) = self.try_hooked(paths);
+            let mut walk_errors = cfg
+                .errors
+                .as_ref()
+                .map_or_else(Vec::new, |e| std::mem::take(&mut *e.lock().unwrap()));
+            walk_errors.append(&mut errors);
+            CollectReport {
+                paths,
+                errors: walk_errors,
+            }
+        }
+        #[inline]
+        fn with_dir_hook(mut self, hook_fn: fn(&Path) -> DirAction) -> CollectFilesConfigured {
+            self.dir_hook = Some(hook_fn);
+            self
+        }
+        #[inline]
+        fn with_error_handler(
+            mut self,
+            handler: fn(&Path, io::Error) -> ErrorAction,
+        ) -> CollectFilesConfigured {
+            self.error_handler = Some(handler);
+            self
+        }
+        #[inline]
+        fn with_retries(mut self, n: usize, backoff: Duration) -> CollectFilesConfigured {
+            self.retries = n;
+            self.backoff = backoff;
+            self
+        }
+        fn collect_into(&self, target: &mut Vec<PathBuf>) {
+            // these build lists of their own.
+            if !self.roots.is_empty() || self.sample.is_some() || self.has_post_filters() {
+                target.extend(self.collect());
+                return;
+            }
+            let start = target.len();
+            let cfg = Arc::new(self.walk_config());
+            self.in_pool(|| self.walk_into(&cfg, target));
+            target.extend(cfg.take_entries());
+            self.hook_tail(target, start);
+        }
+        #[inline]
+        fn collect_as<C: FromParallelIterator<PathBuf>>(&self) -> C {
+            let (mut paths, entries) =
+                self.in_pool(|| self.collect_split_sized(self.capacity_hint()));
+            paths.extend(entries);
+            self.hooked(paths)
+        }
+        #[inline]
+        fn collect_compact(&self) -> CompactPaths {
+            self.collect_as()
+        }
+        #[inline]
+        fn collect_interned(&self) -> InternedPaths {
+            self.collect_as()
+        }
+        #[inline]
+        fn estimate(&self) -> Estimate {
+            if self.depth_zero {
+                return Estimate::default();
+            }
+            let cfg = WalkConfig {
+                dir_hook: None,
+                depth_prune: None,
+                error_handler: None,
+                unwrap_or_else: None,
+                // unreadable directories count as empty.
+                errors: Some(Mutex::new(Vec::new())),
+                ..self.walk_config()
+            };
+            estimate::estimate(&self.root_dir, self.depth, &cfg)
+        }
+        #[inline]
+        fn with_capacity_hint(mut self, files: usize) -> CollectFilesConfigured {
+            self.capacity = files;
+            self
+        }
+        #[inline]
+        fn with_max_open_dirs(mut self, n: usize) -> CollectFilesConfigured {
+            self.open_dirs = Some(Arc::new(limit::Semaphore::new(n)));
+            self
+        }
+        #[inline]
+        fn with_io_throttle(mut self, ops_per_sec: u32) -> CollectFilesConfigured {
+            self.throttle = Some(Arc::new(limit::Pacer::new(ops_per_sec)));
+            self
+        }
+        #[inline]
+        fn with_adaptive_parallelism(mut self, yes: bool) -> CollectFilesConfigured {
+            self.devices = yes.then(|| Arc::new(device::Devices::default()));
+            self
+        }
+        #[inline]
+        fn with_depth_override(mut self, regex: &str, depth: usize) -> CollectFilesConfigured {
+            let regex =
+                Regex::new(regex).unwrap_or_else(|_| panic!("* Regular Expression: {}", regex));
+            self.depth_overrides.push((regex, depth));
+            self
+        }
+        #[inline]
+        fn with_depth_prune(mut self, prune: fn(&Path, usize) -> bool) -> CollectFilesConfigured {
+            self.depth_prune = Some(prune);
+            self
+        }
+        fn stream_by_dir(&self) -> DirBatches {
+            // without threads the whole walk runs before the first batch is read.
+            #[cfg(not(all(target_family = "wasm", not(target_feature = "atomics"))))]
+            let (tx, rx) =
+                mpsc::sync_channel(self.channel_capacity.unwrap_or(stream::BATCH_BUFFER));
+            #[cfg(all(target_family = "wasm", not(target_feature = "atomics")))]
+            let (tx, rx) = mpsc::channel();
+            let configs = self.root_configs();
+            let pool = self.pool.clone();
+            let worker = spawn_worker(move || {
+                let walks: Vec<(&CollectFilesConfigured, WalkConfig)> = configs
+                    .iter()
+                    .filter(|c| !c.depth_zero)
+                    .map(|c| (c, c.walk_config()))
+                    .collect();
+                let send = |batch| tx.send(batch).is_ok();
+                let send = &send;
+                let scope = || {
+                    rayon::scope(|s| {
+                        for (c, cfg) in &walks {
+                            let root = (c.root_dir.clone(), c.depth);
+                            s.spawn(move |s| c.stream_dir(s, cfg, send, root));
                         }
-                    }
-                    Some(_) => vec![PathBuf::default()],
-                    None => vec![path],
+                    })
+                };
+                match pool {
+                    Some(pool) => pool.install(scope),
+                    None => scope(),
                 }
+            });
+            DirBatches::new(rx, worker)
+        }
+        fn collect_with<V: FileVisitor>(&self, visitor: V) -> V {
+            for c in self.root_configs().iter().filter(|c| !c.depth_zero) {
+                let cfg = WalkConfig {
+                    errors: Some(Mutex::new(Vec::new())),
+                    ..c.walk_config()
+                };
+                self.in_pool(|| visit::visit(c.root_dir.clone(), c.depth, &cfg, &visitor));
+            }
+            visitor
+        }
+        fn fold<A, ID, F, R>(&self, identity: ID, fold_op: F, reduce_op: R) -> A
+        where
+            A: Send,
+            ID: Fn() -> A + Sync + Send,
+            F: Fn(A, PathBuf) -> A + Sync + Send,
+            R: Fn(A, A) -> A + Sync + Send,
+        {
+            let hooked_fold = |acc: A, path: PathBuf| {
+                if self.hooks.is_empty() {
+                    return fold_op(acc, path);
+                }
+                match hook::run(&self.hooks, path.clone()) {
+                    Ok(Some(p)) => fold_op(acc, p),
+                    Ok(None) => acc,
+                    Err(e) => panic!("* {}", CollectError::new(path, Operation::Hook, e)),
+                }
+            };
+            let ops = (identity, hooked_fold, reduce_op);
+            self.in_pool(|| {
+                self.root_configs()
+                    .par_iter()
+                    .filter(|c| !c.depth_zero)
+                    .map(|c| {
+                        let cfg = c.walk_config();
+                        let acc = fold_files(c.root_dir.clone(), c.depth, &cfg, &ops);
+                        cfg.take_entries().into_iter().fold(acc, &ops.1)
+                    })
+                    .reduce(&ops.0, &ops.2)
+            })
+        }
+        fn map_reduce<U, M, R>(&self, map_fn: M, reduce_fn: R) -> Option<U>
+        where
+            U: Send,
+            M: Fn(PathBuf) -> U + Sync + Send,
+            R: Fn(U, U) -> U + Sync + Send,
+        {
+            let merge = |a: Option<U>, b: Option<U>| match (a, b) {
+                (Some(a), Some(b)) => Some(reduce_fn(a, b)),
+                (a, None) => a,
+                (None, b) => b,
+            };
+            self.fold(|| None, |acc, path| merge(acc, Some(map_fn(path))), merge)
+        }
+        fn explain(&self) -> Explain {
+            let mut filters = Vec::new();
+            #[cfg(feature = "archive")]
+            if self.descend_archives {
+                filters.push("archive entries".to_owned());
+            }
+            if let Some(mimes) = &self.mime_filter {
+                filters.push(format!("mime {}", mimes.join(", ")));
+            }
+            match self.content_class {
+                Some(content::ContentClass::Text) => filters.push("text only".to_owned()),
+                Some(content::ContentClass::Binary) => filters.push("binary only".to_owned()),
+                None => {}
+            }
+            if self.resolve_symlinks {
+                filters.push("resolve symlinks".to_owned());
+            }
+            if let Some(min) = self.min_nlink {
+                filters.push(format!("at least {} hard links", min));
+            }
+            if let Some(max) = self.max_path_len {
+                filters.push(format!("paths up to {} bytes", max));
+            }
+            if self.skip_macos_files {
+                filters.push("skip macOS metadata".to_owned());
+            }
+            if self.sparse_only {
+                filters.push("sparse only".to_owned());
+            }
+            if self.dedupe_hardlinks {
+                filters.push("dedupe hardlinks".to_owned());
+            }
+            if self.dedupe_case {
+                filters.push("dedupe case-insensitive".to_owned());
+            }
+            match self.sample {
+                Some(sample::Sample::Count(n)) => filters.push(format!("sample {}", n)),
+                Some(sample::Sample::Rate(rate)) => filters.push(format!("sample rate {}", rate)),
+                None => {}
+            }
+            let cfg = WalkConfig {
+                errors: Some(Mutex::new(Vec::new())),
+                ..self.walk_config()
+            };
+            Explain {
+                root: self.root_dir.clone(),
+                max_depth: match (self.depth_zero, self.depth) {
+                    (true, _) => Some(0),
+                    (false, depth) => depth.map(|d| d.saturating_add(1)),
+                },
+                target_regex: self.target_regex.as_ref().map(|r| r.as_str().to_owned()),
+                depth_overrides: self
+                    .depth_overrides
+                    .iter()
+                    .map(|(r, depth)| (r.as_str().to_owned(), *depth))
+                    .collect(),
+                dir_hook: self.dir_hook.is_some(),
+                depth_prune: self.depth_prune.is_some(),
+                skip_hidden: self.skip_hidden,
+                ignore_files: self.ignore_files,
+                prune_dirs: self.prune_dirs.clone(),
+                follow_symlinks: !(cfg!(unix) && self.openat),
+                confine_to_root: self.confine_to_root,
+                traversal: self.strategy,
+                stable_order: self.stable_order,
+                filters,
+                hooks: self.hooks.len(),
+                pruned: if self.depth_zero {
+                    Vec::new()
+                } else {
+                    explain::sample_pruned(&self.root_dir, self.depth, &cfg)
+                },
+            }
+        }
+        #[inline]
+        fn with_skip_hidden(mut self, yes: bool) -> CollectFilesConfigured {
+            self.skip_hidden = yes;
+            self
+        }
+        #[inline]
+        fn with_prune_dirs(mut self, names: &[&str]) -> CollectFilesConfigured {
+            self.prune_dirs.extend(names.iter().map(|n| n.to_string()));
+            self
+        }
+        #[inline]
+        fn with_ignore_files(mut self, yes: bool) -> CollectFilesConfigured {
+            self.ignore_files = yes;
+            self
+        }
+        #[inline]
+        fn with_resolve_symlinks(mut self, yes: bool) -> CollectFilesConfigured {
+            self.resolve_symlinks = yes;
+            self
+        }
+        fn collect_entries(&self) -> Vec<FileEntry> {
+            let unresolved = CollectFilesConfigured {
+                resolve_symlinks: false,
+                ..self.clone()
+            };
+            let paths = unresolved.collect_matched();
+            entry::entries(
+                if self.resolve_symlinks {
+                    entry::resolve(paths)
+                } else {
+                    paths.into_iter().map(|p| (p, None)).collect()
+                },
+                self.dereference,
+            )
+        }
+        #[inline]
+        fn with_dereference(mut self, yes: bool) -> CollectFilesConfigured {
+            self.dereference = yes;
+            self
+        }
+        #[inline]
+        fn with_min_nlink(mut self, n: u64) -> CollectFilesConfigured {
+            self.min_nlink = Some(n);
+            self
+        }
+        #[inline]
+        fn with_sparse_only(mut self) -> CollectFilesConfigured {
+            self.sparse_only = true;
+            self
+        }
+        #[inline]
+        fn with_skip_macos_metadata(mut self) -> CollectFilesConfigured {
+            self.skip_macos_files = true;
+            self.with_prune_dirs(preset::MACOS_DIRS)
+        }
+        fn collect_volumes(&self) -> Vec<PathBuf> {
+            let skip_mounts = volume::pseudo_mounts();
+            let collectors: Vec<CollectFilesConfigured> = volume::outermost(volumes())
+                .into_iter()
+                .map(|root_dir| CollectFilesConfigured {
+                    root_dir,
+                    skip_mounts: skip_mounts.clone(),
+                    ..self.clone()
+                })
+                .collect();
+            collect_all_merged(&collectors)
+        }
+        #[inline]
+        fn with_normalize_separators(mut self, yes: bool) -> CollectFilesConfigured {
+            self.normalize_separators = yes;
+            self
+        }
+        #[inline]
+        fn with_dedupe_case_insensitive(mut self, yes: bool) -> CollectFilesConfigured {
+            self.dedupe_case = yes;
+            self
+        }
+        #[inline]
+        fn with_max_path_len(mut self, n: usize) -> CollectFilesConfigured {
+            self.max_path_len = Some(n);
+            self
+        }
+        #[inline]
+        fn with_confine_to_root(mut self, yes: bool) -> CollectFilesConfigured {
+            self.confine_to_root = yes;
+            self
+        }
+        #[inline]
+        fn with_openat(mut self, yes: bool) -> CollectFilesConfigured {
+            self.openat = yes;
+            self
+        }
+        #[inline]
+        fn with_rebase<P: AsRef<Path>, Q: AsRef<Path>>(
+            mut self,
+            old_root: P,
+            new_prefix: Q,
+        ) -> CollectFilesConfigured {
+            self.hooks.push(hook::Hook::Rebase(
+                old_root.as_ref().to_path_buf(),
+                new_prefix.as_ref().to_path_buf(),
+            ));
+            self
+        }
+        #[inline]
+        fn check_access(&self, children: bool) -> AccessReport {
+            access::check(&self.root_dir, children)
+        }
+        #[cfg(feature = "sqlite")]
+        #[inline]
+        fn write_sqlite<P: AsRef<Path>>(&self, db: P, hash: Option<HashAlgo>) -> io::Result<()> {
+            sqlite::write(self.collect_entries(), db.as_ref(), hash)
+        }
+        #[cfg(feature = "parquet")]
+        #[inline]
+        fn write_parquet<P: AsRef<Path>>(&self, out: P, hash: Option<HashAlgo>) -> io::Result<()> {
+            parquet::write(self.collect_entries(), out.as_ref(), hash)
+        }
+        #[inline]
+        fn print_streaming(&self) -> io::Result<()> {
+            stream::print(self.stream_by_dir())
+        }
+        fn histogram_by_size(&self, buckets: &[u64]) -> Vec<Bucket<u64>> {
+            let bounds = report::bounds(buckets);
+            let unhooked = CollectFilesConfigured {
+                hooks: Vec::new(),
+                ..self.clone()
+            };
+            let tally = unhooked.fold(
+                || vec![(0, 0); bounds.len() + 1],
+                |tally, path| match fs::metadata(&path) {
+                    Ok(meta) => report::count(tally, &bounds, meta.len(), meta.len()),
+                    // gone since it was listed.
+                    Err(_) => tally,
+                },
+                report::merge_tallies,
+            );
+            report::buckets(&bounds, tally)
+        }
+        fn histogram_by_age(&self, buckets: &[Duration]) -> Vec<Bucket<Duration>> {
+            let bounds = report::bounds(buckets);
+            let now = SystemTime::now();
+            let unhooked = CollectFilesConfigured {
+                hooks: Vec::new(),
+                ..self.clone()
+            };
+            let tally = unhooked.fold(
+                || vec![(0, 0); bounds.len() + 1],
+                |tally, path| match fs::metadata(&path) {
+                    Ok(meta) => {
+                        let age = meta
+                            .modified()
+                            .ok()
+                            .and_then(|t| now.duration_since(t).ok())
+                            .unwrap_or_default();
+                        report::count(tally, &bounds, age, meta.len())
+                    }
+                    Err(_) => tally,
+                },
+                report::merge_tallies,
+            );
+            report::buckets(&bounds, tally)
+        }
+        #[inline]
+        fn with_root<P: AsRef<Path>>(
+            mut self,
+            root: P,
+            overrides: fn(CollectFilesConfigured) -> CollectFilesConfigured,
+        ) -> CollectFilesConfigured {
+            self.roots.push((root.as_ref().to_path_buf(), overrides));
+            self
+        }
+        #[inline]
+        fn with_threads(mut self, n: usize) -> CollectFilesConfigured {
+            // the shared pool stays, no thread can be spawned there.
+            if cfg!(all(target_family = "wasm", not(target_feature = "atomics"))) {
+                return self;
+            }
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .unwrap_or_else(|e| panic!("* Thread Pool: {}", e));
+            self.pool = Some(Arc::new(pool));
+            self
+        }
+        fn from_env(self) -> CollectFilesConfigured {
+            let mut this = self;
+            if let Some(n) = env::threads() {
+                this = this.with_threads(n);
+            }
+            if let Some(yes) = env::skip_hidden() {
+                this = this.with_skip_hidden(yes);
+            }
+            this.prune_dirs.extend(env::exclude());
+            this
+        }
+        #[inline]
+        fn with_rename_template(mut self, regex: &str, template: &str) -> CollectFilesConfigured {
+            let regex =
+                Regex::new(regex).unwrap_or_else(|_| panic!("* Regular Expression: {}", regex));
+            self.hooks.push(hook::Hook::Template(
+                regex,
+                template.to_owned(),
+                self.root_dir.clone(),
+            ));
+            self
+        }
+        #[inline]
+        fn with_template(mut self, template: &str) -> CollectFilesConfigured {
+            let template = template::Template::parse(template, self.root_dir.clone());
+            self.hooks.push(hook::Hook::Tokens(template));
+            self
+        }
+        fn preview_hook(&self) -> Vec<(PathBuf, PathBuf)> {
+            self.collect_matched()
+                .into_par_iter()
+                .filter_map(|p| {
+                    let hooked = hook::run(&self.hooks, p.clone()).unwrap_or_else(|e| {
+                        panic!("* {}", CollectError::new(p.clone(), Operation::Hook, e))
+                    });
+                    hooked.map(|h| (p, h))
+                })
+                .collect()
+        }
+        #[inline]
+        fn transform_contents<F>(&self, transform: F, mode: Mode) -> ActionReport
+        where
+            F: Fn(&[u8]) -> io::Result<Vec<u8>> + Sync,
+        {
+            let backup = self.backup();
+            let rewrite =
+                |paths| actions::transform_contents(paths, &transform, mode, backup.as_ref());
+            self.lock_policy
+                .settle(rewrite(self.collect_matched()), rewrite)
+        }
+        #[inline]
+        fn with_backup_dir<P: AsRef<Path>>(mut self, backup_dir: P) -> CollectFilesConfigured {
+            self.backup_dir = Some(backup_dir.as_ref().to_path_buf());
+            self
+        }
+        #[inline]
+        fn with_lock_policy(mut self, policy: LockPolicy) -> CollectFilesConfigured {
+            self.lock_policy = policy;
+            self
+        }
+        #[cfg(feature = "sftp")]
+        fn collect_sftp(&self, sftp: &ssh2::Sftp) -> CollectReport {
+            // the local-only parts of the walk have nothing to look at on the remote side.
+            let cfg = WalkConfig {
+                backend: backend::BackendRef(sftp),
+                errors: Some(Mutex::new(Vec::new())),
+                ignores: None,
+                confine: None,
+                devices: None,
+                descend_archives: false,
+                ..self.walk_config()
+            };
+            let walked = if self.depth_zero {
+                Vec::new()
+            } else {
+                collect_files(self.root_dir.clone(), self.depth, &cfg)
+            };
+            let (paths, mut errors) = self.try_hooked(walked);
+            let mut walk_errors = cfg
+                .errors
+                .map_or_else(Vec::new, |e| e.into_inner().unwrap());
+            walk_errors.append(&mut errors);
+            CollectReport {
+                paths,
+                errors: walk_errors,
+            }
+        }
+        #[cfg(feature = "cache")]
+        fn collect_cached<P: AsRef<Path>>(&self, cache: P) -> io::Result<Vec<PathBuf>> {
+            let cache = cache.as_ref();
+            let fingerprint = self.fingerprint();
+            if let Some(paths) = cache::read(cache, &fingerprint) {
+                return Ok(paths);
+            }
+            let paths = self.collect();
+            cache::write(cache, &fingerprint, &paths)?;
+            Ok(paths)
+        }
+        fn for_each_batch<F>(&self, batch_size: usize, sink: F)
+        where
+            F: Fn(Vec<PathBuf>) + Sync,
+        {
+            let sink = stream::BatchSink::new(batch_size, &sink);
+            let rest = self.fold(Vec::new, |b, p| sink.push(b, p), |a, b| sink.merge(a, b));
+            sink.finish(rest);
+        }
+        #[inline]
+        fn with_channel_capacity(mut self, n: usize) -> CollectFilesConfigured {
+            self.channel_capacity = Some(n);
+            self
+        }
+        #[inline]
+        fn with_split_large_dirs(mut self, chunk: usize) -> CollectFilesConfigured {
+            self.split_dirs = Some(chunk.max(1));
+            self
+        }
+    }
+    impl CollectFilesConfigured {
+        /// runs the hook chain, panicking on the first failure of `with_try_hook`.
+        fn hooked<C: FromParallelIterator<PathBuf>>(&self, paths: Vec<PathBuf>) -> C {
+            if self.hooks.is_empty() {
+                return paths.into_par_iter().collect();
+            }
+            paths
+                .into_par_iter()
+                .filter_map(|p| {
+                    hook::run(&self.hooks, p.clone()).unwrap_or_else(|e| {
+                        panic!("* {}", CollectError::new(p, Operation::Hook, e))
+                    })
+                })
+                .collect()
+        }
+        /// `hooked` on `paths[start..]` in place.
+        fn hook_tail(&self, paths: &mut Vec<PathBuf>, start: usize) {
+            if self.hooks.is_empty() {
+                return;
+            }
+            let kept: Vec<bool> = paths[start..]
+                .par_iter_mut()
+                .map(|p| match hook::run(&self.hooks, p.clone()) {
+                    Ok(Some(hooked)) => {
+                        *p = hooked;
+                        true
+                    }
+                    Ok(None) => false,
+                    Err(e) => panic!("* {}", CollectError::new(p.clone(), Operation::Hook, e)),
+                })
+                .collect();
+            // `retain` visits in order, the first `start` paths were there before.
+            let (mut i, mut kept) = (0, kept.into_iter());
+            paths.retain(|_| {
+                i += 1;
+                i <= start || kept.next() == Some(true)
+            });
+        }
+        /// runs the hook chain, failures of `with_try_hook` are set aside.
+        fn try_hooked(&self, paths: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<CollectError>) {
+            if self.hooks.is_empty() {
+                return (paths, Vec::new());
+            }
+            let results: Vec<_> = paths
+                .into_par_iter()
+                .map(|p| (p.clone(), hook::run(&self.hooks, p)))
+                .collect();
+            let mut report = (Vec::new(), Vec::new());
+            for (path, result) in results {
+                match result {
+                    Ok(Some(p)) => report.0.push(p),
+                    Ok(None) => {}
+                    Err(e) => report.1.push(CollectError::new(path, Operation::Hook, e)),
+                }
+            }
+            report
+        }
+        /// matched files before the hook is applied.
+        #[inline]
+        pub(crate) fn collect_matched(&self) -> Vec<PathBuf> {
+            self.collect_split().0
+        }
+        #[inline]
+        fn walk_config(&self) -> WalkConfig<'static> {
+            WalkConfig {
+                backend: Default::default(),
+                target_regex: self.target_regex.clone(),
+                unwrap_or_else: self.unwrap_or_else,
+                descend_archives: self.descend_archives,
+                stable: self.stable_order,
+                dir_hook: self.dir_hook,
+                error_handler: self.error_handler,
+                errors: None,
+                #[cfg(feature = "archive")]
+                archive_entries: Mutex::new(Vec::new()),
+                retries: self.retries,
+                backoff: self.backoff,
+                open_dirs: self.open_dirs.clone(),
+                throttle: self.throttle.clone(),
+                devices: self.devices.clone(),
+                depth_overrides: self.depth_overrides.clone(),
+                root: self.root_dir.clone(),
+                depth_prune: self.depth_prune,
+                skip_hidden: self.skip_hidden,
+                prune_dirs: self.prune_dirs.clone(),
+                ignores: self.ignore_files.then(ignore::Ignores::default),
+                skip_macos_files: self.skip_macos_files,
+                skip_mounts: self.skip_mounts.clone(),
+                normalize_separators: self.normalize_separators,
+                max_path_len: self.max_path_len,
+                confine: self.confine_to_root.then(|| {
+                    fs::canonicalize(&self.root_dir).unwrap_or_else(|_| self.root_dir.clone())
+                }),
+                split_dirs: self.split_dirs,
+            }
+        }
+        /// sends the batch of `dir`, then walks its subdirectories unless nobody listens anymore.
+        fn stream_dir<'s>(
+            &'s self,
+            s: &rayon::Scope<'s>,
+            cfg: &'s WalkConfig,
+            send: &'s (dyn Fn(Vec<PathBuf>) -> bool + Sync),
+            (dir, depth): cursor::Pending,
+        ) {
+            let ((files, entries), dirs) = read_one(dir, depth, cfg);
+            let mut paths = self.filtered(files);
+            paths.extend(entries);
+            let mut batch: Vec<PathBuf> = self.hooked(paths);
+            if !batch.is_empty() {
+                batch.sort();
+                if !send(batch) {
+                    return;
+                }
+            }
+            for pending in dirs {
+                s.spawn(move |s| self.stream_dir(s, cfg, send, pending));
+            }
+        }
+        /// the main root then every `with_root`, each configured on its own.
+        fn root_configs(&self) -> Vec<CollectFilesConfigured> {
+            let main = CollectFilesConfigured {
+                roots: Vec::new(),
+                ..self.clone()
+            };
+            let extra = self.roots.iter().map(|(root_dir, overrides)| {
+                let root = overrides(CollectFilesConfigured {
+                    root_dir: root_dir.clone(),
+                    ..main.clone()
+                });
+                CollectFilesConfigured {
+                    hooks: self.hooks.clone(),
+                    roots: Vec::new(),
+                    ..root
+                }
+            });
+            std::iter::once(main.clone()).chain(extra).collect()
+        }
+        /// identifies everything deciding what `collect` returns, for `collect_cached`.
+        /// Hooks and other functions count by their place in the binary, so a rebuild
+        /// invalidates the cache.
+        #[cfg(feature = "cache")]
+        fn fingerprint(&self) -> [u8; 32] {
+            let at = |f: Option<usize>| f.map(cache::fn_offset);
+            let hooks: Vec<String> = self
+                .hooks
+                .iter()
+                .map(|h| match h {
+                    hook::Hook::Map(f) => format!("map {}", cache::fn_offset(*f as usize)),
+                    hook::Hook::FilterMap(f) => {
+                        format!("filter_map {}", cache::fn_offset(*f as usize))
+                    }
+                    hook::Hook::Try(f) => format!("try {}", cache::fn_offset(*f as usize)),
+                    other => format!("{:?}", other),
+                })
+                .collect();
+            let roots: Vec<(&PathBuf, usize)> = self
+                .roots
+                .iter()
+                .map(|(root, f)| (root, cache::fn_offset(*f as usize)))
+                .collect();
+            let walk = (
+                (
+                    &self.root_dir,
+                    self.depth,
+                    self.depth_zero,
+                    &self.depth_overrides,
+                ),
+                (
+                    &self.target_regex,
+                    self.normalize_separators,
+                    self.max_path_len,
+                ),
+                (
+                    at(self.unwrap_or_else.map(|f| f as usize)),
+                    at(self.error_handler.map(|f| f as usize)),
+                ),
+                (
+                    at(self.dir_hook.map(|f| f as usize)),
+                    at(self.depth_prune.map(|f| f as usize)),
+                ),
+                (
+                    self.skip_hidden,
+                    &self.prune_dirs,
+                    self.ignore_files,
+                    self.skip_macos_files,
+                ),
+                (
+                    &self.skip_mounts,
+                    self.confine_to_root,
+                    self.openat,
+                    self.descend_archives,
+                ),
+                (self.strategy, self.stable_order, &roots),
+            );
+            let filters = (
+                (&self.mime_filter, self.content_class, self.lock_policy),
+                (self.resolve_symlinks, self.min_nlink, self.sparse_only),
+                (self.dedupe_hardlinks, self.dedupe_case, self.sample),
+            );
+            let described = format!("{:?} {:?} {:?}", walk, filters, hooks);
+            *blake3::hash(described.as_bytes()).as_bytes()
+        }
+        #[inline]
+        fn backup(&self) -> Option<actions::Backup> {
+            let dir = self.backup_dir.as_ref()?;
+            Some(actions::Backup::new(dir, &self.root_dir))
+        }
+        /// runs `op` on the pool of `with_threads`.
+        fn in_pool<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+            match &self.pool {
+                Some(pool) => pool.install(op),
+                None => op(),
+            }
+        }
+        /// (files on disk, entries inside archives)
+        #[inline]
+        fn collect_split(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+            self.collect_split_sized(0)
+        }
+        /// `collect_split` with the walk sized for `capacity` files.
+        fn collect_split_sized(&self, capacity: usize) -> (Vec<PathBuf>, Vec<PathBuf>) {
+            self.in_pool(|| {
+                if let Some(s) = self.sample.filter(|_| self.samples_in_walk()) {
+                    let (sampler, entries) = self
+                        .root_configs()
+                        .par_iter()
+                        .map(|c| c.walk_sampled(s))
+                        .reduce(
+                            || (sample::Sampler::new(s), Vec::new()),
+                            |(a, mut entries), (b, more)| {
+                                entries.extend(more);
+                                (a.merge(b), entries)
+                            },
+                        );
+                    return (sampler.into_paths(), entries);
+                }
+                if self.roots.is_empty() {
+                    let cfg = Arc::new(self.walk_config());
+                    let walked = self.walk(&cfg, capacity);
+                    return (self.filtered(walked), cfg.take_entries());
+                }
+                let (files, entries): (Vec<_>, Vec<_>) = self
+                    .root_configs()
+                    .par_iter()
+                    .map(|c| c.collect_split())
+                    .unzip();
+                (files.concat(), entries.concat())
+            })
+        }
+        /// whether the sample can be drawn on the walking threads: every filter before
+        /// it looks at one file at a time, and the traversal is the usual one.
+        fn samples_in_walk(&self) -> bool {
+            !(self.resolve_symlinks
+                || self.dedupe_hardlinks
+                || self.dedupe_case
+                || (cfg!(unix) && self.openat))
+        }
+        /// the walk of this root with the file filters run on every match, and a
+        /// reservoir per worker merged as the workers finish. (sample, archive entries)
+        fn walk_sampled(&self, s: sample::Sample) -> (sample::Sampler, Vec<PathBuf>) {
+            let identity = || sample::Sampler::new(s);
+            if self.depth_zero {
+                return (identity(), Vec::new());
+            }
+            let fold_op = |mut sampler: sample::Sampler, path: PathBuf| {
+                if self.keeps_file(&path) {
+                    sampler.push(path);
+                }
+                sampler
+            };
+            let ops = (identity, fold_op, sample::Sampler::merge);
+            let cfg = self.walk_config();
+            let sampler = fold_files(self.root_dir.clone(), self.depth, &cfg, &ops);
+            (sampler, cfg.take_entries())
+        }
+        /// the file filters of `filtered` that look at one file at a time.
+        fn keeps_file(&self, path: &Path) -> bool {
+            self.mime_filter
+                .as_ref()
+                .is_none_or(|mimes| content::is_mime(path, mimes, self.lock_policy))
+                && self
+                    .content_class
+                    .is_none_or(|class| content::is_class(path, class, self.lock_policy))
+                && self
+                    .min_nlink
+                    .is_none_or(|min| hardlinks::has_nlink(path, min))
+                && (!self.sparse_only || entry::is_sparse(path))
+        }
+        /// every file the configured traversal reaches, the result of the depth-first walk
+        /// allocated for `capacity` paths up front.
+        #[inline]
+        fn walk(&self, cfg: &Arc<WalkConfig>, capacity: usize) -> Vec<PathBuf> {
+            let mut found = Vec::with_capacity(capacity);
+            self.walk_into(cfg, &mut found);
+            found
+        }
+        /// `walk` appending to `found`, the depth-first walk writing into it directly.
+        fn walk_into(&self, cfg: &Arc<WalkConfig>, found: &mut Vec<PathBuf>) {
+            if self.depth_zero {
+                return;
+            }
+            #[cfg(unix)]
+            if self.openat {
+                found.append(&mut openat::collect_files(&self.root_dir, self.depth, cfg));
+                return;
+            }
+            match self.strategy {
+                Traversal::Dfs => collect_files_into(self.root_dir.clone(), self.depth, cfg, found),
+                Traversal::Bfs => found.append(&mut collect_files_bfs(
+                    self.root_dir.clone(),
+                    self.depth,
+                    cfg,
+                )),
+            }
+        }
+        /// the capacity of `with_capacity_hint`, for a walk filling one result.
+        fn capacity_hint(&self) -> usize {
+            let single = self.roots.is_empty() && self.sample.is_none();
+            let dfs = self.strategy == Traversal::Dfs && !(cfg!(unix) && self.openat);
+            if self.depth_zero || !single || !dfs {
+                return 0;
+            }
+            self.capacity
+        }
+        /// whether `filtered` has a filter to run besides sampling.
+        fn has_post_filters(&self) -> bool {
+            self.resolve_symlinks
+                || self.mime_filter.is_some()
+                || self.content_class.is_some()
+                || self.min_nlink.is_some()
+                || self.sparse_only
+                || self.dedupe_hardlinks
+                || self.dedupe_case
+        }
+        /// the files on disk the post-walk filters keep.
+        fn filtered(&self, mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+            if self.resolve_symlinks {
+                paths = entry::resolve(paths).into_iter().map(|(p, _)| p).collect();
+            }
+            if let Some(mimes) = &self.mime_filter {
+                paths = content::filter_mime(paths, mimes, self.lock_policy);
+            }
+            if let Some(class) = self.content_class {
+                paths = content::filter_class(paths, class, self.lock_policy);
+            }
+            if let Some(min) = self.min_nlink {
+                paths = hardlinks::filter_nlink(paths, min);
+            }
+            if self.sparse_only {
+                paths = entry::filter_sparse(paths);
+            }
+            if self.dedupe_hardlinks {
+                paths = hardlinks::dedupe(paths);
+            }
+            if self.dedupe_case {
+                paths = hardlinks::dedupe_case(paths);
+            }
+            if let Some(s) = self.sample.filter(|_| !self.samples_in_walk()) {
+                paths = sample::sample(paths, s);
+            }
+            paths
+        }
+    }
+}
+
+/// CollectFiles(`entry_dir`)
+///
+/// ## Example
+/// ```ignore
+/// use collectfiles::*;
+///
+/// let vec = CollectFiles("/Users/hwakyeom/programs/")
+///         .with_depth(1)
+///         .with_target_regex(".md$")
+///         .with_hook(|path| path.with_extension("mutated"))
+///         .with_unwrap_or_else(|e| {
+///             if e.kind() == io::ErrorKind::NotFound {
+///                 PathBuf::from("/Users/other/")
+///             } else {
+///                panic!("{:?}", e)
+///             }
+///         })
+///         .collect();
+///
+/// println!("{:#?}", vec);
+/// ```
+#[derive(Debug)]
+pub struct CollectFiles<T>(pub T)
+where
+    T: AsRef<Path> + Clone + Send + Sync;
+
+impl<T> CollectFiles<T>
+where
+    T: AsRef<Path> + Clone + Send + Sync,
+{
+    fn clone(&self) -> CollectFilesConfigured {
+        CollectFilesConfigured::new(self.0.as_ref().to_path_buf())
+    }
+    /// Source files by extension, skipping hidden files and the tool caches `target`,
+    /// `node_modules`, `__pycache__`, `.git`, `.hg`, `.svn` and `.venv`.
+    /// Names like `build` or `vendor` may hold sources and are walked.
+    pub fn source_code(root_dir: T) -> CollectFilesConfigured {
+        CollectFiles(root_dir).preset(preset::SOURCE_CODE)
+    }
+    /// Images, audio and video by extension, skipping hidden files and
+    /// the tool caches of `source_code`.
+    pub fn media(root_dir: T) -> CollectFilesConfigured {
+        CollectFiles(root_dir).preset(preset::MEDIA)
+    }
+    /// Text, office and PDF documents by extension, skipping hidden files and
+    /// the tool caches of `source_code`.
+    pub fn documents(root_dir: T) -> CollectFilesConfigured {
+        CollectFiles(root_dir).preset(preset::DOCUMENTS)
+    }
+    fn preset(&self, extensions: &[&str]) -> CollectFilesConfigured {
+        self.clone()
+            .with_target_regex(&preset::extensions_regex(extensions))
+            .with_skip_hidden(true)
+            .with_prune_dirs(preset::PRUNED_DIRS)
+    }
+}
+
+impl<T> CollectFilesPrelude for CollectFiles<T>
+where
+    T: AsRef<Path> + Clone + Send + Sync,
+{
+    #[inline]
+    fn as_target_regex(&self) -> Option<&str> {
+        None
+    }
+    #[inline]
+    fn as_root_dir(&self) -> &Path {
+        self.0.as_ref()
+    }
+    #[inline]
+    fn as_hook(&self) -> Option<fn(PathBuf) -> PathBuf> {
+        None
+    }
+    #[inline]
+    fn as_depth(&self) -> Option<usize> {
+        None
+    }
+    #[inline]
+    fn with_hook(self, hook_fn: fn(PathBuf) -> PathBuf) -> CollectFilesConfigured {
+        self.clone().with_hook(hook_fn)
+    }
+    #[inline]
+    fn with_depth(self, level: usize) -> CollectFilesConfigured {
+        self.clone().with_depth(level)
+    }
+    #[inline]
+    fn with_max_depth(self, n: usize) -> CollectFilesConfigured {
+        self.clone().with_max_depth(n)
+    }
+    #[inline]
+    fn with_target_regex(self, regex: &str) -> CollectFilesConfigured {
+        self.clone().with_target_regex(regex)
+    }
+    #[inline]
+    fn with_unwrap_or_else(self, f: fn(io::Error) -> PathBuf) -> CollectFilesConfigured {
+        self.clone().with_unwrap_or_else(f)
+    }
+    #[inline]
+    fn with_dedupe_hardlinks(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_dedupe_hardlinks(yes)
+    }
+    #[inline]
+    fn collect(&self) -> Vec<PathBuf> {
+        collect_files(self.0.as_ref().to_path_buf(), None, &WalkConfig::default())
+    }
+    #[inline]
+    fn collect_hashed(&self, algo: HashAlgo) -> ContentReport<Hash> {
+        hash::hash_files(self.collect(), algo, LockPolicy::Error)
+    }
+    #[inline]
+    fn collect_duplicates(&self) -> Vec<Vec<PathBuf>> {
+        self.collect_duplicates_by(DupeStage::Full)
+    }
+    #[inline]
+    fn collect_duplicates_by(&self, stage: DupeStage) -> Vec<Vec<PathBuf>> {
+        dupes::find_duplicates(self.collect(), stage)
+    }
+    #[inline]
+    fn collect_name_collisions(&self) -> Vec<Vec<PathBuf>> {
+        dupes::find_name_collisions(self.collect())
+    }
+    #[inline]
+    fn write_manifest<W: Write>(&self, writer: W, algo: HashAlgo) -> io::Result<()> {
+        self.clone().write_manifest(writer, algo)
+    }
+    #[inline]
+    fn verify_manifest<R: BufRead>(&self, reader: R, algo: HashAlgo) -> io::Result<VerifyReport> {
+        hash::verify_manifest(self.collect(), self.0.as_ref(), algo, reader)
+    }
+    #[inline]
+    fn copy_to<P: AsRef<Path>>(&self, dest_root: P, collision: Collision) -> ActionReport {
+        actions::copy_to(
+            self.collect(),
+            self.0.as_ref(),
+            dest_root.as_ref(),
+            collision,
+        )
+    }
+    #[inline]
+    fn move_to<P: AsRef<Path>>(
+        &self,
+        dest_root: P,
+        collision: Collision,
+        mode: Mode,
+    ) -> ActionReport {
+        actions::move_to(
+            self.collect(),
+            self.0.as_ref(),
+            dest_root.as_ref(),
+            collision,
+            mode,
+        )
+    }
+    #[inline]
+    fn delete(&self, mode: Mode, prune_empty_dirs: bool) -> DeleteReport {
+        actions::delete(
+            self.collect(),
+            self.0.as_ref(),
+            mode,
+            prune_empty_dirs,
+            None,
+        )
+    }
+    #[cfg(feature = "trash")]
+    #[inline]
+    fn delete_to_trash(&self, mode: Mode) -> DeleteReport {
+        actions::delete_to_trash(self.collect(), mode)
+    }
+    #[inline]
+    fn remove_broken_symlinks(&self, mode: Mode) -> DeleteReport {
+        actions::remove_broken_symlinks(self.collect(), mode)
+    }
+    #[inline]
+    fn remove_empty_dirs(&self, mode: Mode) -> DeleteReport {
+        self.clone().remove_empty_dirs(mode)
+    }
+    #[inline]
+    fn apply_rename(&self, mode: Mode) -> ActionReport {
+        actions::apply_rename(self.collect(), &[], mode, None)
+    }
+    #[inline]
+    fn link_into<P: AsRef<Path>>(&self, dest_root: P, kind: LinkKind) -> ActionReport {
+        actions::link_into(self.collect(), self.0.as_ref(), dest_root.as_ref(), kind)
+    }
+    #[cfg(feature = "archive")]
+    #[inline]
+    fn write_tar<W: Write>(&self, writer: W, compression: TarCompression) -> io::Result<()> {
+        archive::write_tar(self.collect(), self.0.as_ref(), writer, compression)
+    }
+    #[cfg(feature = "archive")]
+    #[inline]
+    fn write_zip<W: Write + io::Seek>(
+        &self,
+        writer: W,
+        level: u32,
+        prefix: &str,
+    ) -> io::Result<()> {
+        archive::write_zip(self.collect(), self.0.as_ref(), writer, level, prefix)
+    }
+    #[cfg(unix)]
+    #[inline]
+    fn set_permissions(&self, perm: u32, mode: Mode) -> ActionReport {
+        actions::set_permissions(self.collect(), perm, mode)
+    }
+    #[cfg(unix)]
+    #[inline]
+    fn set_owner(&self, uid: Option<u32>, gid: Option<u32>, mode: Mode) -> ActionReport {
+        actions::set_owner(self.collect(), uid, gid, mode)
+    }
+    #[inline]
+    fn touch(&self, time: SystemTime, mode: Mode) -> ActionReport {
+        actions::touch(self.collect(), time, mode)
+    }
+    #[inline]
+    fn write_files_from<W: Write>(&self, writer: W, nul: bool) -> io::Result<()> {
+        export::write_files_from(self.collect(), self.0.as_ref(), writer, nul)
+    }
+    #[inline]
+    fn collect_set(&self, canonicalize: bool) -> PathSet {
+        self.clone().collect_set(canonicalize)
+    }
+    #[inline]
+    fn compare<P: AsRef<Path>>(&self, other_root: P, by: CompareBy) -> TreeDiff {
+        self.clone().compare(other_root, by)
+    }
+    #[inline]
+    fn collect_usage(&self) -> Vec<(PathBuf, u64)> {
+        self.clone().collect_usage()
+    }
+    #[inline]
+    fn summarize_by_extension(&self) -> Vec<ExtSummary> {
+        self.clone().summarize_by_extension()
+    }
+    #[inline]
+    fn collect_line_counts(&self, max_bytes: u64) -> ContentReport<u64> {
+        content::line_counts(self.collect(), max_bytes, LockPolicy::Error)
+    }
+    #[inline]
+    fn with_mime_filter(self, mimes: &[&str]) -> CollectFilesConfigured {
+        self.clone().with_mime_filter(mimes)
+    }
+    #[inline]
+    fn with_text_only(self) -> CollectFilesConfigured {
+        self.clone().with_text_only()
+    }
+    #[inline]
+    fn with_binary_only(self) -> CollectFilesConfigured {
+        self.clone().with_binary_only()
+    }
+    #[inline]
+    fn collect_encodings(&self) -> ContentReport<Encoding> {
+        content::encodings(self.collect(), LockPolicy::Error)
+    }
+    #[inline]
+    fn collect_contents(&self, limit_bytes: u64, budget_bytes: u64) -> ContentReport<Vec<u8>> {
+        self.clone().collect_contents(limit_bytes, budget_bytes)
+    }
+    #[inline]
+    fn collect_strings(&self, limit_bytes: u64, budget_bytes: u64) -> ContentReport<String> {
+        self.clone().collect_strings(limit_bytes, budget_bytes)
+    }
+    #[cfg(feature = "mmap")]
+    #[inline]
+    fn collect_mmaps(&self) -> ContentReport<Mmap> {
+        content::mmaps(self.collect(), LockPolicy::Error)
+    }
+    #[inline]
+    fn collect_previews(&self, n: usize, budget_bytes: u64) -> ContentReport<Vec<u8>> {
+        content::previews(self.collect(), n, budget_bytes, LockPolicy::Error)
+    }
+    #[cfg(feature = "archive")]
+    #[inline]
+    fn with_descend_archives(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_descend_archives(yes)
+    }
+    #[inline]
+    fn with_strategy(self, strategy: Traversal) -> CollectFilesConfigured {
+        self.clone().with_strategy(strategy)
+    }
+    #[inline]
+    fn with_stable_order(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_stable_order(yes)
+    }
+    #[inline]
+    fn cursor(&self) -> Cursor {
+        Cursor::new(self.0.as_ref().to_path_buf(), None)
+    }
+    #[inline]
+    fn collect_batch(&self, cursor: &mut Cursor, max_dirs: usize) -> Vec<PathBuf> {
+        self.clone().collect_batch(cursor, max_dirs)
+    }
+    #[inline]
+    fn resume(&self, cursor: Cursor) -> Vec<PathBuf> {
+        self.clone().resume(cursor)
+    }
+    #[inline]
+    fn with_sample(self, n: usize) -> CollectFilesConfigured {
+        self.clone().with_sample(n)
+    }
+    #[inline]
+    fn with_sample_rate(self, rate: f64) -> CollectFilesConfigured {
+        self.clone().with_sample_rate(rate)
+    }
+    #[inline]
+    fn with_filter_map_hook(
+        self,
+        hook_fn: fn(PathBuf) -> Option<PathBuf>,
+    ) -> CollectFilesConfigured {
+        self.clone().with_filter_map_hook(hook_fn)
+    }
+    #[inline]
+    fn with_try_hook(self, hook_fn: fn(PathBuf) -> io::Result<PathBuf>) -> CollectFilesConfigured {
+        self.clone().with_try_hook(hook_fn)
+    }
+    #[inline]
+    fn try_collect(&self) -> CollectReport {
+        self.clone().try_collect()
+    }
+    #[inline]
+    fn with_dir_hook(self, hook_fn: fn(&Path) -> DirAction) -> CollectFilesConfigured {
+        self.clone().with_dir_hook(hook_fn)
+    }
+    #[inline]
+    fn with_error_handler(
+        self,
+        handler: fn(&Path, io::Error) -> ErrorAction,
+    ) -> CollectFilesConfigured {
+        self.clone().with_error_handler(handler)
+    }
+    #[inline]
+    fn with_retries(self, n: usize, backoff: Duration) -> CollectFilesConfigured {
+        self.clone().with_retries(n, backoff)
+    }
+    #[inline]
+    fn collect_into(&self, target: &mut Vec<PathBuf>) {
+        collect_files_into(
+            self.0.as_ref().to_path_buf(),
+            None,
+            &WalkConfig::default(),
+            target,
+        );
+    }
+    #[inline]
+    fn collect_as<C: FromParallelIterator<PathBuf>>(&self) -> C {
+        self.collect().into_par_iter().collect()
+    }
+    #[inline]
+    fn collect_compact(&self) -> CompactPaths {
+        self.collect_as()
+    }
+    #[inline]
+    fn collect_interned(&self) -> InternedPaths {
+        self.collect_as()
+    }
+    #[inline]
+    fn estimate(&self) -> Estimate {
+        self.clone().estimate()
+    }
+    #[inline]
+    fn with_capacity_hint(self, files: usize) -> CollectFilesConfigured {
+        self.clone().with_capacity_hint(files)
+    }
+    #[inline]
+    fn with_max_open_dirs(self, n: usize) -> CollectFilesConfigured {
+        self.clone().with_max_open_dirs(n)
+    }
+    #[inline]
+    fn with_io_throttle(self, ops_per_sec: u32) -> CollectFilesConfigured {
+        self.clone().with_io_throttle(ops_per_sec)
+    }
+    #[inline]
+    fn with_adaptive_parallelism(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_adaptive_parallelism(yes)
+    }
+    #[inline]
+    fn with_depth_override(self, regex: &str, depth: usize) -> CollectFilesConfigured {
+        self.clone().with_depth_override(regex, depth)
+    }
+    #[inline]
+    fn with_depth_prune(self, prune: fn(&Path, usize) -> bool) -> CollectFilesConfigured {
+        self.clone().with_depth_prune(prune)
+    }
+    #[inline]
+    fn stream_by_dir(&self) -> DirBatches {
+        self.clone().stream_by_dir()
+    }
+    #[inline]
+    fn collect_with<V: FileVisitor>(&self, visitor: V) -> V {
+        self.clone().collect_with(visitor)
+    }
+    #[inline]
+    fn fold<A, ID, F, R>(&self, identity: ID, fold_op: F, reduce_op: R) -> A
+    where
+        A: Send,
+        ID: Fn() -> A + Sync + Send,
+        F: Fn(A, PathBuf) -> A + Sync + Send,
+        R: Fn(A, A) -> A + Sync + Send,
+    {
+        self.clone().fold(identity, fold_op, reduce_op)
+    }
+    #[inline]
+    fn map_reduce<U, M, R>(&self, map_fn: M, reduce_fn: R) -> Option<U>
+    where
+        U: Send,
+        M: Fn(PathBuf) -> U + Sync + Send,
+        R: Fn(U, U) -> U + Sync + Send,
+    {
+        self.clone().map_reduce(map_fn, reduce_fn)
+    }
+    #[inline]
+    fn explain(&self) -> Explain {
+        self.clone().explain()
+    }
+    #[inline]
+    fn with_skip_hidden(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_skip_hidden(yes)
+    }
+    #[inline]
+    fn with_prune_dirs(self, names: &[&str]) -> CollectFilesConfigured {
+        self.clone().with_prune_dirs(names)
+    }
+    #[inline]
+    fn with_ignore_files(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_ignore_files(yes)
+    }
+    #[inline]
+    fn with_resolve_symlinks(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_resolve_symlinks(yes)
+    }
+    #[inline]
+    fn collect_entries(&self) -> Vec<FileEntry> {
+        self.clone().collect_entries()
+    }
+    #[inline]
+    fn with_dereference(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_dereference(yes)
+    }
+    #[inline]
+    fn with_min_nlink(self, n: u64) -> CollectFilesConfigured {
+        self.clone().with_min_nlink(n)
+    }
+    #[inline]
+    fn with_sparse_only(self) -> CollectFilesConfigured {
+        self.clone().with_sparse_only()
+    }
+    #[inline]
+    fn with_skip_macos_metadata(self) -> CollectFilesConfigured {
+        self.clone().with_skip_macos_metadata()
+    }
+    #[inline]
+    fn collect_volumes(&self) -> Vec<PathBuf> {
+        self.clone().collect_volumes()
+    }
+    #[inline]
+    fn with_normalize_separators(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_normalize_separators(yes)
+    }
+    #[inline]
+    fn with_dedupe_case_insensitive(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_dedupe_case_insensitive(yes)
+    }
+    #[inline]
+    fn with_max_path_len(self, n: usize) -> CollectFilesConfigured {
+        self.clone().with_max_path_len(n)
+    }
+    #[inline]
+    fn with_confine_to_root(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_confine_to_root(yes)
+    }
+    #[inline]
+    fn with_openat(self, yes: bool) -> CollectFilesConfigured {
+        self.clone().with_openat(yes)
+    }
+    #[inline]
+    fn with_rebase<P: AsRef<Path>, Q: AsRef<Path>>(
+        self,
+        old_root: P,
+        new_prefix: Q,
+    ) -> CollectFilesConfigured {
+        self.clone().with_rebase(old_root, new_prefix)
+    }
+    #[inline]
+    fn check_access(&self, children: bool) -> AccessReport {
+        access::check(self.0.as_ref(), children)
+    }
+    #[cfg(feature = "sqlite")]
+    #[inline]
+    fn write_sqlite<P: AsRef<Path>>(&self, db: P, hash: Option<HashAlgo>) -> io::Result<()> {
+        self.clone().write_sqlite(db, hash)
+    }
+    #[cfg(feature = "parquet")]
+    #[inline]
+    fn write_parquet<P: AsRef<Path>>(&self, out: P, hash: Option<HashAlgo>) -> io::Result<()> {
+        self.clone().write_parquet(out, hash)
+    }
+    #[inline]
+    fn print_streaming(&self) -> io::Result<()> {
+        self.clone().print_streaming()
+    }
+    #[inline]
+    fn histogram_by_size(&self, buckets: &[u64]) -> Vec<Bucket<u64>> {
+        self.clone().histogram_by_size(buckets)
+    }
+    #[inline]
+    fn histogram_by_age(&self, buckets: &[Duration]) -> Vec<Bucket<Duration>> {
+        self.clone().histogram_by_age(buckets)
+    }
+    #[inline]
+    fn with_root<P: AsRef<Path>>(
+        self,
+        root: P,
+        overrides: fn(CollectFilesConfigured) -> CollectFilesConfigured,
+    ) -> CollectFilesConfigured {
+        self.clone().with_root(root, overrides)
+    }
+    #[inline]
+    fn with_threads(self, n: usize) -> CollectFilesConfigured {
+        self.clone().with_threads(n)
+    }
+    #[inline]
+    fn from_env(self) -> CollectFilesConfigured {
+        self.clone().from_env()
+    }
+    #[inline]
+    fn with_rename_template(self, regex: &str, template: &str) -> CollectFilesConfigured {
+        self.clone().with_rename_template(regex, template)
+    }
+    #[inline]
+    fn with_template(self, template: &str) -> CollectFilesConfigured {
+        self.clone().with_template(template)
+    }
+    #[inline]
+    fn preview_hook(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.collect().into_iter().map(|p| (p.clone(), p)).collect()
+    }
+    #[inline]
+    fn transform_contents<F>(&self, transform: F, mode: Mode) -> ActionReport
+    where
+        F: Fn(&[u8]) -> io::Result<Vec<u8>> + Sync,
+    {
+        actions::transform_contents(self.collect(), transform, mode, None)
+    }
+    #[inline]
+    fn with_backup_dir<P: AsRef<Path>>(self, backup_dir: P) -> CollectFilesConfigured {
+        self.clone().with_backup_dir(backup_dir)
+    }
+    #[inline]
+    fn with_lock_policy(self, policy: LockPolicy) -> CollectFilesConfigured {
+        self.clone().with_lock_policy(policy)
+    }
+    #[cfg(feature = "sftp")]
+    #[inline]
+    fn collect_sftp(&self, sftp: &ssh2::Sftp) -> CollectReport {
+        self.clone().collect_sftp(sftp)
+    }
+    #[cfg(feature = "cache")]
+    #[inline]
+    fn collect_cached<P: AsRef<Path>>(&self, cache: P) -> io::Result<Vec<PathBuf>> {
+        self.clone().collect_cached(cache)
+    }
+    #[inline]
+    fn for_each_batch<F>(&self, batch_size: usize, sink: F)
+    where
+        F: Fn(Vec<PathBuf>) + Sync,
+    {
+        self.clone().for_each_batch(batch_size, sink)
+    }
+    #[inline]
+    fn with_channel_capacity(self, n: usize) -> CollectFilesConfigured {
+        self.clone().with_channel_capacity(n)
+    }
+    #[inline]
+    fn with_split_large_dirs(self, chunk: usize) -> CollectFilesConfigured {
+        self.clone().with_split_large_dirs(chunk)
+    }
+}
+
+/// Runs several configured collections on the one shared rayon pool,
+/// results in the order of `collectors`.
+pub fn collect_all(collectors: &[CollectFilesConfigured]) -> Vec<Vec<PathBuf>> {
+    collectors.par_iter().map(|c| c.collect()).collect()
+}
+
+/// `collect_all` merged into one sorted list without duplicates.
+pub fn collect_all_merged(collectors: &[CollectFilesConfigured]) -> Vec<PathBuf> {
+    let mut merged: Vec<PathBuf> = collect_all(collectors).into_iter().flatten().collect();
+    merged.par_sort();
+    merged.dedup();
+    merged
+}
+
+/// runs `job` on a thread of its own, or right away where threads cannot be
+/// spawned (wasm without atomics, e.g. `wasm32-wasip1`).
+pub(crate) fn spawn_worker<F: FnOnce() + Send + 'static>(job: F) -> Option<thread::JoinHandle<()>> {
+    #[cfg(not(all(target_family = "wasm", not(target_feature = "atomics"))))]
+    return Some(thread::spawn(job));
+    #[cfg(all(target_family = "wasm", not(target_feature = "atomics")))]
+    {
+        job();
+        None
+    }
+}
+
+/// Settings shared by every directory of one walk, built once per walk.
+#[derive(Debug, Default)]
+struct WalkConfig<'b> {
+    /// where directories are read from.
+    backend: backend::BackendRef<'b>,
+    target_regex: Option<Regex>,
+    unwrap_or_else: Option<fn(io::Error) -> PathBuf>,
+    #[cfg_attr(not(feature = "archive"), allow(dead_code))]
+    descend_archives: bool,
+    stable: bool,
+    dir_hook: Option<fn(&Path) -> DirAction>,
+    error_handler: Option<fn(&Path, io::Error) -> ErrorAction>,
+    /// set by `try_collect`, unreadable directories are reported here instead of panicking.
+    errors: Option<Mutex<Vec<CollectError>>>,
+    /// virtual `archive!/entry` paths the walk found, kept apart from the files on disk.
+    #[cfg(feature = "archive")]
+    archive_entries: Mutex<Vec<PathBuf>>,
+    retries: usize,
+    backoff: Duration,
+    open_dirs: Option<Arc<limit::Semaphore>>,
+    throttle: Option<Arc<limit::Pacer>>,
+    devices: Option<Arc<device::Devices>>,
+    depth_overrides: Vec<(Regex, usize)>,
+    /// for the levels handed to `depth_prune`.
+    root: PathBuf,
+    depth_prune: Option<fn(&Path, usize) -> bool>,
+    skip_hidden: bool,
+    prune_dirs: Vec<String>,
+    ignores: Option<ignore::Ignores>,
+    skip_macos_files: bool,
+    skip_mounts: Vec<PathBuf>,
+    normalize_separators: bool,
+    max_path_len: Option<usize>,
+    /// the canonical root under `with_confine_to_root`.
+    confine: Option<PathBuf>,
+    /// entries per chunk under `with_split_large_dirs`.
+    split_dirs: Option<usize>,
+}
+
+impl WalkConfig<'_> {
+    /// the archive entries found so far, leaving none behind.
+    #[cfg(feature = "archive")]
+    #[inline]
+    fn take_entries(&self) -> Vec<PathBuf> {
+        std::mem::take(&mut *self.archive_entries.lock().unwrap())
+    }
+    #[cfg(not(feature = "archive"))]
+    #[inline]
+    fn take_entries(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+    /// adds archive entries to the ones the walk found.
+    #[cfg(feature = "archive")]
+    #[inline]
+    fn found_entries(&self, mut entries: Vec<PathBuf>) {
+        if !entries.is_empty() {
+            self.archive_entries.lock().unwrap().append(&mut entries);
+        }
+    }
+    #[cfg(not(feature = "archive"))]
+    #[inline]
+    fn found_entries(&self, _entries: Vec<PathBuf>) {}
+    /// runs `op` again after transient failures, up to `retries` times, doubling the pause each time.
+    fn retry<T>(&self, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut op = || {
+            if let Some(pacer) = &self.throttle {
+                pacer.wait();
+            }
+            op()
+        };
+        let mut pause = self.backoff;
+        for _ in 0..self.retries {
+            match op() {
+                Err(e) if is_transient(&e) => {
+                    thread::sleep(pause);
+                    pause *= 2;
+                }
+                result => return result,
+            }
+        }
+        op()
+    }
+    /// `Path::is_dir` with retries.
+    #[inline]
+    fn is_dir(&self, path: &Path) -> bool {
+        self.retry(|| fs::metadata(path))
+            .map(|m| m.is_dir())
+            .unwrap_or(false)
+    }
+    /// a symlink resolving outside the confined root, or not resolving at all.
+    fn escapes(&self, link: &Path) -> bool {
+        match &self.confine {
+            Some(root) => fs::canonicalize(link).map_or(true, |target| !target.starts_with(root)),
+            None => false,
+        }
+    }
+    /// `read_dir_each` collected.
+    fn read_dir(&self, dir: PathBuf) -> Vec<(PathBuf, bool)> {
+        let mut entries = Vec::new();
+        self.read_dir_each(dir, &mut |entry| entries.push(entry));
+        entries
+    }
+    /// `open(dir)`, failures going to the error handler, then to `unwrap_or_else`.
+    /// `None` when the directory is skipped, otherwise the directory finally opened.
+    fn open_dir<T>(
+        &self,
+        mut dir: PathBuf,
+        open: impl Fn(&Path) -> io::Result<T>,
+    ) -> Option<(PathBuf, T)> {
+        loop {
+            let e = match self.retry(|| open(&dir)) {
+                Ok(opened) => return Some((dir, opened)),
+                Err(e) => e,
+            };
+            match (self.error_handler, self.unwrap_or_else) {
+                (Some(handler), _) => match self.handle(handler, &dir, e) {
+                    ErrorAction::Retry => {}
+                    ErrorAction::Skip => return None,
+                    ErrorAction::Substitute(other) => dir = other,
+                    ErrorAction::Abort => unreachable!(),
+                },
+                (None, Some(f)) => {
+                    let other = f(e);
+                    if other.as_os_str().is_empty() {
+                        return None;
+                    }
+                    // the fallback directory gets a single try.
+                    return match self.retry(|| open(&other)) {
+                        Ok(opened) => Some((other, opened)),
+                        Err(e) => {
+                            self.fail(other, e);
+                            None
+                        }
+                    };
+                }
+                (None, None) => {
+                    self.fail(dir, e);
+                    return None;
+                }
+            }
+        }
+    }
+    /// entries of `dir` and whether each one is a directory, handed to `each` as they
+    /// are read, failures go to the error handler, then to `unwrap_or_else`.
+    fn read_dir_each(&self, dir: PathBuf, each: &mut dyn FnMut((PathBuf, bool))) {
+        // the handle is closed before returning, the permit covers all of its lifetime.
+        let _permit = self.open_dirs.as_ref().map(|s| s.acquire());
+        let device = self.devices.as_ref().and_then(|d| d.limit_for(&dir));
+        let _device_permit = device.as_ref().map(|s| s.acquire());
+        let backend = self.backend.0;
+        let (dir, read) = match self.open_dir(dir, |d: &Path| backend.read_dir(d, self)) {
+            Some(opened) => opened,
+            None => return,
+        };
+        read.filter_map(|entry| {
+            let e = match entry {
+                Ok(entry) => return Some(entry),
+                Err(e) => e,
+            };
+            let substitute = match (self.error_handler, self.unwrap_or_else) {
+                (Some(handler), _) => match self.handle(handler, &dir, e) {
+                    // a failed entry cannot be listed again on its own.
+                    ErrorAction::Retry | ErrorAction::Skip => None,
+                    ErrorAction::Substitute(other) => Some(other),
+                    ErrorAction::Abort => unreachable!(),
+                },
+                (None, Some(f)) => Some(f(e)).filter(|p| !p.as_os_str().is_empty()),
+                (None, None) => {
+                    self.fail(dir.clone(), e);
+                    None
+                }
+            };
+            substitute.map(|p| {
+                let is_dir = backend.is_dir(&p, self);
+                (p, is_dir)
+            })
+        })
+        .for_each(each)
+    }
+    /// reports an unhandled failure to `errors`, or panics without it.
+    fn fail(&self, dir: PathBuf, e: io::Error) {
+        let e = CollectError::new(dir, Operation::ReadDir, e);
+        match &self.errors {
+            Some(errors) => errors.lock().unwrap().push(e),
+            None => panic!("* {}", e),
+        }
+    }
+    /// asks `handler`, panicking right away on `ErrorAction::Abort`.
+    fn handle(
+        &self,
+        handler: fn(&Path, io::Error) -> ErrorAction,
+        path: &Path,
+        e: io::Error,
+    ) -> ErrorAction {
+        let message = e.to_string();
+        match handler(path, e) {
+            ErrorAction::Abort => panic!("* aborted at {}: {}", path.display(), message),
+            action => action,
+        }
+    }
+    /// the text regexes are matched against, `\\` turned into `/` under `with_normalize_separators`.
+    #[inline]
+    fn subject<'p>(&self, path: &'p str) -> std::borrow::Cow<'p, str> {
+        if self.normalize_separators && path.contains('\\') {
+            path.replace('\\', "/").into()
+        } else {
+            path.into()
+        }
+    }
+    /// remaining depth to walk the subdirectory `dir` with, `None` when it is not entered.
+    #[inline]
+    fn descend(&self, dir: &Path, depth: Option<usize>) -> Option<Option<usize>> {
+        match self.plan(dir, depth) {
+            Ok(depth) => Some(depth),
+            Err(PruneReason::PathTooLong) => {
+                self.too_long(dir);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+    /// whether `path` is over `max_path_len` bytes.
+    #[inline]
+    fn is_too_long(&self, path: &Path) -> bool {
+        self.max_path_len
+            .is_some_and(|max| path.as_os_str().len() > max)
+    }
+    /// reports a path skipped for its length to `try_collect`.
+    fn too_long(&self, path: &Path) {
+        if let (Some(errors), Some(max)) = (&self.errors, self.max_path_len) {
+            let e = io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} bytes, over the limit of {}",
+                    path.as_os_str().len(),
+                    max
+                ),
+            );
+            errors.lock().unwrap().push(CollectError::new(
+                path.to_path_buf(),
+                Operation::PathLength,
+                e,
+            ));
+        }
+    }
+    /// `descend`, telling why a directory is left out.
+    fn plan(&self, dir: &Path, depth: Option<usize>) -> Result<Option<usize>, PruneReason> {
+        if self.is_too_long(dir) {
+            return Err(PruneReason::PathTooLong);
+        }
+        if let Some(name) = dir.file_name() {
+            if self.skip_hidden && is_hidden(name) {
+                return Err(PruneReason::Hidden);
+            }
+            if self.prune_dirs.iter().any(|p| name == p.as_str()) {
+                return Err(PruneReason::Name);
+            }
+        }
+        if self.skip_mounts.iter().any(|m| m == dir) {
+            return Err(PruneReason::PseudoFs);
+        }
+        if let Some(ignores) = &self.ignores {
+            if ignores.is_ignored(&self.root, dir, true) {
+                return Err(PruneReason::Ignored);
+            }
+        }
+        if let Some(prune) = self.depth_prune {
+            let level = dir
+                .strip_prefix(&self.root)
+                .map_or(0, |rel| rel.components().count());
+            if prune(dir, level) {
+                return Err(PruneReason::DepthPrune);
+            }
+        }
+        let action = match self.dir_hook.map_or(DirAction::Descend, |f| f(dir)) {
+            DirAction::Descend => self
+                .depth_overrides
+                .iter()
+                .rev()
+                .find(|(r, _)| r.is_match(&self.subject(&dir.to_string_lossy())))
+                .map_or(DirAction::Descend, |(_, n)| DirAction::DescendWithDepth(*n)),
+            action => action,
+        };
+        match (action, depth) {
+            (DirAction::Skip, _) => Err(PruneReason::DirHook),
+            (DirAction::Descend, Some(0)) => Err(PruneReason::DepthLimit),
+            (DirAction::Descend, Some(dep)) => Ok(Some(dep - 1)),
+            (DirAction::Descend, None) => Ok(None),
+            (DirAction::DescendWithDepth(n), _) => Ok(Some(n)),
+        }
+    }
+}
+
+/// failures worth another try, as seen on SMB/NFS mounts.
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[inline]
+fn collect_files(dir_path: PathBuf, depth: Option<usize>, cfg: &WalkConfig) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    collect_files_into(dir_path, depth, cfg, &mut found);
+    found
+}
+
+/// `collect_files` appending to `found`, which may already be sized for the result.
+fn collect_files_into(
+    dir_path: PathBuf,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+    found: &mut Vec<PathBuf>,
+) {
+    let visit = |(path, is_dir): (PathBuf, bool)| {
+        if is_dir {
+            let found = cfg
+                .descend(&path, depth)
+                .map(|depth| collect_files(path, depth, cfg));
+            return Either::Left(found.unwrap_or_default().into_par_iter());
+        }
+        #[cfg(feature = "archive")]
+        if cfg.descend_archives && archive::is_archive(&path) {
+            return Either::Right(match_archive_into(path, cfg).into_par_iter());
+        }
+        Either::Right(match_file(path, cfg).into_par_iter())
+    };
+
+    // a sorted result needs the whole listing first.
+    if let Some(chunk) = cfg.split_dirs.filter(|_| !cfg.stable) {
+        found.append(&mut collect_chunked(dir_path, chunk, cfg, &visit));
+        return;
+    }
+    let mut paths = cfg.read_dir(dir_path);
+    if cfg.stable {
+        // an indexed parallel iterator keeps the sorted order when collecting.
+        paths.sort();
+    }
+    found.par_extend(paths.into_par_iter().flat_map(visit));
+}
+
+/// hands every `chunk` entries of `dir_path` to other workers while the rest is read.
+fn collect_chunked<V, I>(
+    dir_path: PathBuf,
+    chunk: usize,
+    cfg: &WalkConfig,
+    visit: &V,
+) -> Vec<PathBuf>
+where
+    V: Fn((PathBuf, bool)) -> I + Sync,
+    I: ParallelIterator<Item = PathBuf>,
+{
+    let found = Mutex::new(Vec::new());
+    let visit_chunk = |entries: Vec<(PathBuf, bool)>| {
+        let paths: Vec<PathBuf> = entries.into_par_iter().flat_map(visit).collect();
+        found.lock().unwrap().push(paths);
+    };
+    rayon::scope(|s| {
+        let visit_chunk = &visit_chunk;
+        let mut entries = Vec::with_capacity(chunk);
+        cfg.read_dir_each(dir_path, &mut |entry| {
+            entries.push(entry);
+            if entries.len() == chunk {
+                let full = std::mem::replace(&mut entries, Vec::with_capacity(chunk));
+                s.spawn(move |_| visit_chunk(full));
+            }
+        });
+        visit_chunk(entries);
+    });
+    found.into_inner().unwrap().concat()
+}
+
+/// `collect_files` folding every match into per-thread accumulators instead of collecting.
+fn fold_files<T, ID, F, R>(
+    dir_path: PathBuf,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+    ops: &(ID, F, R),
+) -> T
+where
+    T: Send,
+    ID: Fn() -> T + Sync,
+    F: Fn(T, PathBuf) -> T + Sync,
+    R: Fn(T, T) -> T + Sync,
+{
+    let (identity, fold_op, reduce_op) = ops;
+    cfg.read_dir(dir_path)
+        .into_par_iter()
+        .fold(identity, |acc, (path, is_dir)| {
+            if is_dir {
+                return match cfg.descend(&path, depth) {
+                    Some(depth) => reduce_op(acc, fold_files(path, depth, cfg, ops)),
+                    None => acc,
+                };
+            }
+            #[cfg(feature = "archive")]
+            if cfg.descend_archives && archive::is_archive(&path) {
+                return match match_archive_into(path, cfg) {
+                    Some(path) => fold_op(acc, path),
+                    None => acc,
+                };
+            }
+            match match_file(path, cfg) {
+                Some(path) => fold_op(acc, path),
+                None => acc,
             }
         })
-        .filter(|p| p.as_os_str() != "")
-        .collect()
+        .reduce(identity, reduce_op)
+}
+
+fn collect_files_bfs(dir_path: PathBuf, depth: Option<usize>, cfg: &WalkConfig) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut level = vec![(dir_path, depth)];
+    while !level.is_empty() {
+        let (matched, dirs): (Vec<Matched>, Vec<Vec<cursor::Pending>>) = level
+            .into_par_iter()
+            .map(|(dir, depth)| read_one(dir, depth, cfg))
+            .unzip();
+        for (files, entries) in matched {
+            found.extend(files);
+            cfg.found_entries(entries);
+        }
+        level = dirs.into_iter().flatten().collect();
+    }
+    found
+}
+
+/// matched files of one directory: (files on disk, entries inside archives).
+type Matched = (Vec<PathBuf>, Vec<PathBuf>);
+
+/// matched files of `dir` and the subdirectories to read next.
+fn read_one(
+    dir: PathBuf,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+) -> (Matched, Vec<cursor::Pending>) {
+    let mut entries = cfg.read_dir(dir);
+    if cfg.stable {
+        entries.sort();
+    }
+    let sort = |(path, is_dir): (PathBuf, bool)| -> (Matched, Option<cursor::Pending>) {
+        if is_dir {
+            return (
+                Matched::default(),
+                cfg.descend(&path, depth).map(|depth| (path, depth)),
+            );
+        }
+        #[cfg(feature = "archive")]
+        if cfg.descend_archives && archive::is_archive(&path) {
+            let (archive, entries) = match_archive(path, cfg);
+            return ((archive.into_iter().collect(), entries), None);
+        }
+        (
+            (match_file(path, cfg).into_iter().collect(), Vec::new()),
+            None,
+        )
+    };
+    let (mut matched, mut dirs) = (Matched::default(), Vec::new());
+    let push = |((files, entries), dir): (Matched, Option<cursor::Pending>)| {
+        matched.0.extend(files);
+        matched.1.extend(entries);
+        dirs.extend(dir);
+    };
+    match cfg.split_dirs {
+        Some(chunk) if entries.len() > chunk => {
+            // in order, so a sorted listing stays sorted.
+            let sorted: Vec<_> = entries
+                .into_par_iter()
+                .with_min_len(chunk)
+                .map(sort)
+                .collect();
+            sorted.into_iter().for_each(push);
+        }
+        _ => entries.into_iter().map(sort).for_each(push),
+    }
+    (matched, dirs)
+}
+
+/// dotfiles, the unix convention.
+#[inline]
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+    name.as_encoded_bytes().first() == Some(&b'.')
+}
+
+/// `path` if the target regex accepts it.
+fn match_file(path: PathBuf, cfg: &WalkConfig) -> Option<PathBuf> {
+    if cfg.is_too_long(&path) {
+        cfg.too_long(&path);
+        return None;
+    }
+    if cfg.skip_hidden && path.file_name().is_some_and(is_hidden) {
+        return None;
+    }
+    if cfg.skip_macos_files && path.file_name().is_some_and(preset::is_macos_metadata) {
+        return None;
+    }
+    if let Some(ignores) = &cfg.ignores {
+        if ignores.is_ignored(&cfg.root, &path, false) {
+            return None;
+        }
+    }
+    match &cfg.target_regex {
+        Some(r) => {
+            let name = path
+                .to_str()
+                .unwrap_or_else(|| panic!("* not a valid unicode extension: {}", path.display()));
+            r.is_match(&cfg.subject(name)).then_some(path)
+        }
+        None => Some(path),
+    }
+}
+
+/// (the archive itself if the target regex accepts it, its entries the target regex accepts)
+#[cfg(feature = "archive")]
+fn match_archive(path: PathBuf, cfg: &WalkConfig) -> (Option<PathBuf>, Vec<PathBuf>) {
+    let accepts = |p: &Path| {
+        cfg.target_regex
+            .as_ref()
+            .is_none_or(|r| r.is_match(&cfg.subject(&p.to_string_lossy())))
+    };
+    let mut entries = archive::entries(&path);
+    entries.retain(|p| accepts(p));
+    (accepts(&path).then_some(path), entries)
+}
+
+/// `match_archive` with the entries going to the entries of the walk.
+#[cfg(feature = "archive")]
+fn match_archive_into(path: PathBuf, cfg: &WalkConfig) -> Option<PathBuf> {
+    let (archive, entries) = match_archive(path, cfg);
+    cfg.found_entries(entries);
+    archive
 }
 
 // #[cfg(test)]
@@ -0,0 +1,72 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    sync::{Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Counting semaphore shared by every worker of a walk.
+#[derive(Debug)]
+pub(crate) struct Semaphore {
+    permits: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl Semaphore {
+    #[inline]
+    pub(crate) fn new(permits: usize) -> Self {
+        Self {
+            permits: Mutex::new(permits.max(1)),
+            freed: Condvar::new(),
+        }
+    }
+    /// blocks until a permit is free, it is given back when the guard drops.
+    pub(crate) fn acquire(&self) -> Permit<'_> {
+        let mut permits = self.permits.lock().unwrap();
+        while *permits == 0 {
+            permits = self.freed.wait(permits).unwrap();
+        }
+        *permits -= 1;
+        Permit(self)
+    }
+}
+
+pub(crate) struct Permit<'a>(&'a Semaphore);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self.0.permits.lock().unwrap() += 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// Spaces operations evenly in time, whichever worker asks.
+#[derive(Debug)]
+pub(crate) struct Pacer {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Pacer {
+    #[inline]
+    pub(crate) fn new(ops_per_sec: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / ops_per_sec.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+    /// sleeps until this caller's slot comes.
+    pub(crate) fn wait(&self) {
+        let pause = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + self.interval;
+            slot - now
+        };
+        if !pause.is_zero() {
+            thread::sleep(pause);
+        }
+    }
+}
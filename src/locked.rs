@@ -0,0 +1,93 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{io, path::PathBuf, thread, time::Duration};
+
+/// What happens to a file another process keeps open without sharing it
+/// (Windows sharing and lock violations; nothing is ever locked elsewhere).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LockPolicy {
+    /// the action reports it as failed, content passes in their `errors`,
+    /// content filters leave it out.
+    #[default]
+    Error,
+    /// the action sets it aside in `locked`, content passes leave it out.
+    Skip,
+    /// tries up to `times` more times, pausing `backoff` and then twice as long
+    /// each time, then skips it.
+    Retry { times: usize, backoff: Duration },
+}
+
+#[cfg(windows)]
+pub(crate) fn is_locked(e: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    matches!(
+        e.raw_os_error(),
+        Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+    )
+}
+
+#[cfg(not(windows))]
+#[inline]
+pub(crate) fn is_locked(_e: &io::Error) -> bool {
+    false
+}
+
+/// Reports with a `locked` list that a `LockPolicy` can settle.
+pub(crate) trait Settle {
+    /// removes the failures caused by a lock and returns their paths.
+    fn take_locked(&mut self) -> Vec<PathBuf>;
+    /// adds the outcome of a retry.
+    fn absorb(&mut self, retried: Self);
+    fn push_locked(&mut self, locked: Vec<PathBuf>);
+}
+
+impl LockPolicy {
+    /// runs `op`, `Ok(None)` when the file is locked and to be skipped.
+    pub(crate) fn run<T>(self, mut op: impl FnMut() -> io::Result<T>) -> io::Result<Option<T>> {
+        let (times, mut pause) = match self {
+            LockPolicy::Error => return op().map(Some),
+            LockPolicy::Skip => (0, Duration::ZERO),
+            LockPolicy::Retry { times, backoff } => (times, backoff),
+        };
+        for attempt in 0..=times {
+            match op() {
+                Err(e) if is_locked(&e) => {
+                    if attempt < times {
+                        thread::sleep(pause);
+                        pause *= 2;
+                    }
+                }
+                result => return result.map(Some),
+            }
+        }
+        Ok(None)
+    }
+
+    /// moves the locked failures of `report` to its `locked` list,
+    /// after running `rerun` on them again under `Retry`.
+    pub(crate) fn settle<R, F>(self, mut report: R, rerun: F) -> R
+    where
+        R: Settle,
+        F: Fn(Vec<PathBuf>) -> R,
+    {
+        let (times, mut pause) = match self {
+            LockPolicy::Error => return report,
+            LockPolicy::Skip => (0, Duration::ZERO),
+            LockPolicy::Retry { times, backoff } => (times, backoff),
+        };
+        let mut locked = report.take_locked();
+        for _ in 0..times {
+            if locked.is_empty() {
+                break;
+            }
+            thread::sleep(pause);
+            pause *= 2;
+            let mut retried = rerun(locked);
+            locked = retried.take_locked();
+            report.absorb(retried);
+        }
+        report.push_locked(locked);
+        report
+    }
+}
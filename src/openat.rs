@@ -0,0 +1,228 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+//! Walking by directory descriptors: entries are told apart with `fstatat` relative
+//! to their open directory, never following symlinks, and every directory below the
+//! root is opened with `openat(parent, name, O_NOFOLLOW)` and checked to be the very
+//! one its parent listed (same device and inode). Ancestors are never resolved again,
+//! so swapping one for a symlink while the walk runs cannot lead it anywhere else, and
+//! paths past `PATH_MAX` are walked like any other.
+//!
+//! A directory stays open until its subdirectories are walked, so a worker holds one
+//! descriptor per level it is below the root. `with_max_open_dirs` bounds only the
+//! directories being opened and listed at once: counting the held ones too would
+//! deadlock a tree deeper than the bound.
+
+use std::{
+    ffi::{CStr, CString, OsStr},
+    io, mem,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{match_file, WalkConfig};
+
+/// (device, inode) of a directory.
+type Id = (libc::dev_t, libc::ino_t);
+
+/// An open directory, closed on drop.
+struct Dir(libc::c_int);
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        // SAFETY: the descriptor is owned and closed only here.
+        unsafe { libc::close(self.0) };
+    }
+}
+
+/// the root may be a symlink itself, e.g. `/tmp` on macOS: it is followed.
+const ROOT_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+const DIR_FLAGS: libc::c_int = ROOT_FLAGS | libc::O_NOFOLLOW;
+
+impl Dir {
+    fn open(path: &Path) -> io::Result<Dir> {
+        let path = c_string(path.as_os_str())?;
+        // SAFETY: `path` is a valid C string.
+        match unsafe { libc::open(path.as_ptr(), ROOT_FLAGS) } {
+            -1 => Err(io::Error::last_os_error()),
+            fd => Ok(Dir(fd)),
+        }
+    }
+    /// `name` in this directory, listed as `id`, an error if something else is there now.
+    fn open_listed(&self, name: &CStr, id: Id) -> io::Result<Dir> {
+        // SAFETY: `self.0` is open, `name` a valid C string.
+        let dir = match unsafe { libc::openat(self.0, name.as_ptr(), DIR_FLAGS) } {
+            -1 => return Err(io::Error::last_os_error()),
+            fd => Dir(fd),
+        };
+        let mut stat = mem::MaybeUninit::<libc::stat>::uninit();
+        // SAFETY: `dir.0` is open, `stat` large enough.
+        if unsafe { libc::fstat(dir.0, stat.as_mut_ptr()) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fstat` succeeded and filled it.
+        let stat = unsafe { stat.assume_init() };
+        if (stat.st_dev, stat.st_ino) != id {
+            return Err(io::Error::other(format!(
+                "* {} was replaced during the walk",
+                OsStr::from_bytes(name.to_bytes()).to_string_lossy()
+            )));
+        }
+        Ok(dir)
+    }
+    /// names in the directory with the id of each one that is a directory itself,
+    /// entries gone since they were listed left out.
+    fn entries(&self) -> io::Result<Vec<(CString, Option<Id>)>> {
+        Ok(self
+            .names()?
+            .into_iter()
+            .filter_map(|name| {
+                let dir = self.dir_id_at(&name).ok()?;
+                Some((name, dir))
+            })
+            .collect())
+    }
+    /// names in the directory, `.` and `..` left out.
+    fn names(&self) -> io::Result<Vec<CString>> {
+        // `closedir` closes the descriptor it was given, so it gets its own.
+        // SAFETY: `self.0` is open.
+        let fd = unsafe { libc::dup(self.0) };
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is open and owned by the stream from here on.
+        let stream = unsafe { libc::fdopendir(fd) };
+        if stream.is_null() {
+            let e = io::Error::last_os_error();
+            // SAFETY: `fdopendir` failed, `fd` is still ours.
+            unsafe { libc::close(fd) };
+            return Err(e);
+        }
+        let mut names = Vec::new();
+        let result = loop {
+            clear_errno();
+            // SAFETY: `stream` is open until `closedir` below.
+            let entry = unsafe { libc::readdir(stream) };
+            if entry.is_null() {
+                let e = io::Error::last_os_error();
+                break match e.raw_os_error() {
+                    Some(0) | None => Ok(()),
+                    Some(_) => Err(e),
+                };
+            }
+            // SAFETY: `d_name` is a NUL-terminated string inside `entry`.
+            let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+            if name.to_bytes() != b"." && name.to_bytes() != b".." {
+                names.push(name.to_owned());
+            }
+        };
+        // SAFETY: `stream` came from `fdopendir` and is closed once.
+        unsafe { libc::closedir(stream) };
+        result.map(|()| names)
+    }
+    /// the id of `name` if it is a directory itself, symlinks never counting as one.
+    fn dir_id_at(&self, name: &CStr) -> io::Result<Option<Id>> {
+        let mut stat = mem::MaybeUninit::<libc::stat>::uninit();
+        // SAFETY: `self.0` is open, `name` valid, `stat` large enough.
+        let ret = unsafe {
+            libc::fstatat(
+                self.0,
+                name.as_ptr(),
+                stat.as_mut_ptr(),
+                libc::AT_SYMLINK_NOFOLLOW,
+            )
+        };
+        if ret == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fstatat` succeeded and filled it.
+        let stat = unsafe { stat.assume_init() };
+        Ok((stat.st_mode & libc::S_IFMT == libc::S_IFDIR).then_some((stat.st_dev, stat.st_ino)))
+    }
+}
+
+fn c_string(s: &OsStr) -> io::Result<CString> {
+    CString::new(s.as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// so a `readdir` returning null can tell the end of the directory from a failure.
+#[inline]
+fn clear_errno() {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten"))]
+    // SAFETY: errno is thread-local.
+    unsafe {
+        *libc::__errno_location() = 0
+    };
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    // SAFETY: errno is thread-local.
+    unsafe {
+        *libc::__error() = 0
+    };
+    #[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+    // SAFETY: errno is thread-local.
+    unsafe {
+        *libc::__errno() = 0
+    };
+}
+
+/// `collect_files` walking by descriptors. Symlinks are reported like files and never entered.
+pub(crate) fn collect_files(
+    root_dir: &Path,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+) -> Vec<PathBuf> {
+    collect_dir(root_dir.to_path_buf(), None, depth, cfg)
+}
+
+/// `listed` is the open parent, the name and the id it saw `dir_path` with, `None` for the root.
+fn collect_dir(
+    dir_path: PathBuf,
+    listed: Option<(&Dir, &CStr, Id)>,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+) -> Vec<PathBuf> {
+    let opened = {
+        // the permit covers opening and listing, the descriptor stays open for the levels below.
+        let _permit = cfg.open_dirs.as_ref().map(|s| s.acquire());
+        let expected = dir_path.clone();
+        cfg.open_dir(dir_path, |path: &Path| {
+            let dir = match listed {
+                Some((parent, name, id)) if path == expected => parent.open_listed(name, id)?,
+                // the root, or a directory the error handler put in place.
+                _ => Dir::open(path)?,
+            };
+            let entries = dir.entries()?;
+            Ok((dir, entries))
+        })
+    };
+    let (dir_path, (dir, mut entries)) = match opened {
+        Some(opened) => opened,
+        None => return Vec::new(),
+    };
+    if cfg.stable {
+        entries.sort();
+    }
+    entries
+        .into_par_iter()
+        .flat_map(|(name, id)| {
+            let path = dir_path.join(OsStr::from_bytes(name.to_bytes()));
+            if let Some(id) = id {
+                return match cfg.descend(&path, depth) {
+                    Some(depth) => collect_dir(path, Some((&dir, &name, id)), depth, cfg),
+                    None => Vec::new(),
+                };
+            }
+            #[cfg(feature = "archive")]
+            if cfg.descend_archives && crate::archive::is_archive(&path) {
+                return crate::match_archive_into(path, cfg).into_iter().collect();
+            }
+            match_file(path, cfg).into_iter().collect()
+        })
+        .collect()
+}
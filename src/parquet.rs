@@ -0,0 +1,86 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{fs::File, io, path::Path, sync::Arc};
+
+use parquet::{
+    basic::Compression,
+    data_type::{ByteArray, ByteArrayType, Int64Type},
+    errors::ParquetError,
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+
+use crate::{entry, FileEntry, HashAlgo};
+
+const SCHEMA: &str = "
+message entries {
+    REQUIRED BYTE_ARRAY path (STRING);
+    REQUIRED INT64 size (INTEGER(64, false));
+    OPTIONAL INT64 mtime (TIMESTAMP(MILLIS, true));
+    REQUIRED BYTE_ARRAY type (STRING);
+    OPTIONAL BYTE_ARRAY hash (STRING);
+}
+";
+
+#[inline]
+fn to_io(e: ParquetError) -> io::Error {
+    io::Error::other(e)
+}
+
+/// the present values of an optional column and its definition levels.
+fn optional<T, U>(
+    values: impl Iterator<Item = Option<T>>,
+    f: impl Fn(T) -> U,
+) -> (Vec<U>, Vec<i16>) {
+    let mut present = Vec::new();
+    let levels = values
+        .map(|v| match v {
+            Some(v) => {
+                present.push(f(v));
+                1
+            }
+            None => 0,
+        })
+        .collect();
+    (present, levels)
+}
+
+/// `entries` as one row group of a Parquet file at `out`, snappy compressed.
+pub(crate) fn write(entries: Vec<FileEntry>, out: &Path, algo: Option<HashAlgo>) -> io::Result<()> {
+    let rows = entry::rows(entries, algo);
+    let schema = Arc::new(parse_message_type(SCHEMA).map_err(to_io)?);
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(File::create(out)?, schema, props).map_err(to_io)?;
+    let mut group = writer.next_row_group().map_err(to_io)?;
+
+    let paths: Vec<ByteArray> = rows.iter().map(|r| r.path.as_str().into()).collect();
+    let sizes: Vec<i64> = rows.iter().map(|r| r.size as i64).collect();
+    let (mtimes, mtime_levels) = optional(rows.iter().map(|r| r.mtime), |d| d.as_millis() as i64);
+    let kinds: Vec<ByteArray> = rows.iter().map(|r| r.kind.into()).collect();
+    let (hashes, hash_levels) = optional(rows.iter().map(|r| r.hash.as_deref()), ByteArray::from);
+
+    let mut column = 0;
+    while let Some(mut col) = group.next_column().map_err(to_io)? {
+        match column {
+            0 => col.typed::<ByteArrayType>().write_batch(&paths, None, None),
+            1 => col.typed::<Int64Type>().write_batch(&sizes, None, None),
+            2 => col
+                .typed::<Int64Type>()
+                .write_batch(&mtimes, Some(&mtime_levels), None),
+            3 => col.typed::<ByteArrayType>().write_batch(&kinds, None, None),
+            _ => col
+                .typed::<ByteArrayType>()
+                .write_batch(&hashes, Some(&hash_levels), None),
+        }
+        .map_err(to_io)?;
+        col.close().map_err(to_io)?;
+        column += 1;
+    }
+    group.close().map_err(to_io)?;
+    writer.close().map_err(to_io)?;
+    Ok(())
+}
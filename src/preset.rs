@@ -0,0 +1,46 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::ffi::OsStr;
+
+/// directories no preset descends into: names only tools create, VCS metadata and
+/// dependency caches. `build`, `dist` or `vendor` may be hand-written sources.
+pub(crate) const PRUNED_DIRS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    "node_modules",
+    "target",
+    "__pycache__",
+    ".venv",
+];
+
+pub(crate) const SOURCE_CODE: &[&str] = &[
+    "rs", "c", "h", "cc", "cpp", "hpp", "cs", "go", "java", "kt", "swift", "py", "rb", "php", "js",
+    "jsx", "ts", "tsx", "mjs", "lua", "sh", "zig", "scala", "hs", "ml", "ex", "exs", "dart",
+    "toml", "sql",
+];
+
+pub(crate) const MEDIA: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "heic", "svg", "raw", "mp3", "wav",
+    "flac", "ogg", "m4a", "aac", "mp4", "m4v", "mkv", "mov", "avi", "webm", "wmv",
+];
+
+pub(crate) const DOCUMENTS: &[&str] = &[
+    "txt", "md", "rst", "pdf", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "csv", "ppt",
+    "pptx", "odp", "epub", "tex", "html", "htm",
+];
+
+/// directories macOS keeps on every volume.
+pub(crate) const MACOS_DIRS: &[&str] = &[".Spotlight-V100", ".Trashes", ".fseventsd"];
+
+/// Finder metadata `.DS_Store` and AppleDouble `._*` files.
+#[inline]
+pub(crate) fn is_macos_metadata(name: &OsStr) -> bool {
+    let name = name.as_encoded_bytes();
+    name == b".DS_Store" || name.starts_with(b"._")
+}
+
+/// case-insensitive regex matching any of the extensions.
+pub(crate) fn extensions_regex(extensions: &[&str]) -> String {
+    format!(r"(?i)\.({})$", extensions.join("|"))
+}
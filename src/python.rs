@@ -0,0 +1,49 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+//! Python module `collectfiles`, built with maturin from `pyproject.toml`.
+//!
+//! ```python
+//! import collectfiles
+//! collectfiles.collect("src", regex=r"\.py$", depth=3, exclude=["__pycache__"])
+//! ```
+
+use std::{ffi::OsString, path::PathBuf};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use regex::Regex;
+
+use crate::{private::CollectFilesConfigured, CollectFilesPrelude};
+
+/// Files below `root` as a list of str. `regex` is matched against the full
+/// path, `depth` is the deepest level reported (the root's entries being 1)
+/// and `exclude` holds directory names never entered. Unreadable directories
+/// are skipped like `os.walk` does.
+#[pyfunction]
+#[pyo3(signature = (root, regex=None, depth=None, exclude=None))]
+fn collect(
+    py: Python<'_>,
+    root: PathBuf,
+    regex: Option<&str>,
+    depth: Option<usize>,
+    exclude: Option<Vec<String>>,
+) -> PyResult<Vec<OsString>> {
+    let mut config = CollectFilesConfigured::new(root);
+    if let Some(regex) = regex {
+        Regex::new(regex).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        config = config.with_target_regex(regex);
+    }
+    if let Some(depth) = depth {
+        config = config.with_max_depth(depth);
+    }
+    if let Some(exclude) = &exclude {
+        let names: Vec<&str> = exclude.iter().map(String::as_str).collect();
+        config = config.with_prune_dirs(&names);
+    }
+    let paths = py.allow_threads(|| config.try_collect().paths);
+    Ok(paths.into_iter().map(PathBuf::into_os_string).collect())
+}
+
+#[pymodule]
+fn collectfiles(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(collect, m)?)
+}
@@ -0,0 +1,167 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::HashMap,
+    fs,
+    hash::Hash as StdHash,
+    path::{Path, PathBuf},
+};
+
+/// Per-extension totals produced by `summarize_by_extension`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtSummary {
+    /// without the dot, empty for files without an extension.
+    pub ext: String,
+    pub count: u64,
+    pub total_bytes: u64,
+}
+
+/// One bucket of a histogram: files with a value in `start..end`, `end` being
+/// `None` for the last, open-ended bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bucket<B> {
+    pub start: B,
+    pub end: Option<B>,
+    pub count: u64,
+    pub total_bytes: u64,
+}
+
+/// (count, bytes) of every bucket, one more than there are bounds.
+pub(crate) type Tally = Vec<(u64, u64)>;
+
+/// the bucket boundaries, sorted and without repeats.
+pub(crate) fn bounds<B: Ord + Copy>(buckets: &[B]) -> Vec<B> {
+    let mut bounds = buckets.to_vec();
+    bounds.sort();
+    bounds.dedup();
+    bounds
+}
+
+/// counts a file of `len` bytes whose value is `value` into `tally`.
+#[inline]
+pub(crate) fn count<B: Ord>(mut tally: Tally, bounds: &[B], value: B, len: u64) -> Tally {
+    let bucket = &mut tally[bounds.partition_point(|b| *b <= value)];
+    bucket.0 += 1;
+    bucket.1 += len;
+    tally
+}
+
+pub(crate) fn merge_tallies(mut a: Tally, b: Tally) -> Tally {
+    for (acc, v) in a.iter_mut().zip(b) {
+        acc.0 += v.0;
+        acc.1 += v.1;
+    }
+    a
+}
+
+/// `tally` labelled with the ranges of `bounds`, starting from `B::default()`.
+pub(crate) fn buckets<B: Copy + Default>(bounds: &[B], tally: Tally) -> Vec<Bucket<B>> {
+    let starts = std::iter::once(B::default()).chain(bounds.iter().copied());
+    let ends = bounds
+        .iter()
+        .copied()
+        .map(Some)
+        .chain(std::iter::once(None));
+    starts
+        .zip(ends)
+        .zip(tally)
+        .map(|((start, end), (count, total_bytes))| Bucket {
+            start,
+            end,
+            count,
+            total_bytes,
+        })
+        .collect()
+}
+
+/// merges per-thread maps built by `fold`.
+fn merge<K: StdHash + Eq, V, F: Fn(&mut V, V)>(
+    mut a: HashMap<K, V>,
+    b: HashMap<K, V>,
+    add: F,
+) -> HashMap<K, V> {
+    for (k, v) in b {
+        match a.get_mut(&k) {
+            Some(acc) => add(acc, v),
+            None => {
+                a.insert(k, v);
+            }
+        }
+    }
+    a
+}
+
+/// the size of `path`, `None` for a broken link or a file gone since it was listed.
+#[inline]
+fn file_len(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().map(|m| m.len())
+}
+
+/// bytes under every directory, as folded by `collect_usage`.
+pub(crate) type Usage = HashMap<PathBuf, u64>;
+
+/// adds the size of `path` to every directory above it, up to `root_dir`.
+pub(crate) fn add_usage(mut acc: Usage, path: &Path, root_dir: &Path) -> Usage {
+    let len = match file_len(path) {
+        Some(len) => len,
+        None => return acc,
+    };
+    for dir in path.ancestors().skip(1) {
+        *acc.entry(dir.to_path_buf()).or_default() += len;
+        if dir == root_dir || !dir.starts_with(root_dir) {
+            break;
+        }
+    }
+    acc
+}
+
+#[inline]
+pub(crate) fn merge_usage(a: Usage, b: Usage) -> Usage {
+    merge(a, b, |acc, v| *acc += v)
+}
+
+/// the totals sorted by directory.
+pub(crate) fn usage(totals: Usage) -> Vec<(PathBuf, u64)> {
+    let mut totals: Vec<(PathBuf, u64)> = totals.into_iter().collect();
+    totals.sort();
+    totals
+}
+
+/// (count, bytes) per extension, as folded by `summarize_by_extension`.
+pub(crate) type ExtTotals = HashMap<String, (u64, u64)>;
+
+/// counts `path` under its extension.
+pub(crate) fn add_extension(mut acc: ExtTotals, path: &Path) -> ExtTotals {
+    if let Some(len) = file_len(path) {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let entry = acc.entry(ext).or_default();
+        entry.0 += 1;
+        entry.1 += len;
+    }
+    acc
+}
+
+#[inline]
+pub(crate) fn merge_extensions(a: ExtTotals, b: ExtTotals) -> ExtTotals {
+    merge(a, b, |acc, v| {
+        acc.0 += v.0;
+        acc.1 += v.1;
+    })
+}
+
+/// the totals, largest first.
+pub(crate) fn by_extension(totals: ExtTotals) -> Vec<ExtSummary> {
+    let mut summary: Vec<ExtSummary> = totals
+        .into_iter()
+        .map(|(ext, (count, total_bytes))| ExtSummary {
+            ext,
+            count,
+            total_bytes,
+        })
+        .collect();
+    summary.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes).then(a.ext.cmp(&b.ext)));
+    summary
+}
@@ -0,0 +1,120 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How many matches `with_sample` / `with_sample_rate` keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Sample {
+    Count(usize),
+    Rate(f64),
+}
+
+/// splitmix64, plenty for picking files.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    /// `stream` keeps generators created at the same instant apart.
+    pub(crate) fn from_clock(stream: u64) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Rng(nanos ^ 0x9E37_79B9_7F4A_7C15 ^ stream.wrapping_mul(0xD1B5_4A32_D192_ED03))
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// uniform in `0..n`.
+    #[inline]
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+    /// uniform in `0.0..1.0`.
+    #[inline]
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// keeps every match with the same probability, one per worker of the walk,
+/// reservoir sampling (algorithm R) for `Sample::Count`.
+pub(crate) struct Sampler {
+    sample: Sample,
+    /// matches pushed, the kept ones included.
+    seen: usize,
+    kept: Vec<PathBuf>,
+    rng: Rng,
+}
+
+impl Sampler {
+    pub(crate) fn new(sample: Sample) -> Self {
+        // a stream per sampler, workers start at the same instant.
+        static STREAMS: AtomicU64 = AtomicU64::new(0);
+        Self {
+            sample,
+            seen: 0,
+            kept: Vec::new(),
+            rng: Rng::from_clock(STREAMS.fetch_add(1, Ordering::Relaxed)),
+        }
+    }
+    pub(crate) fn push(&mut self, path: PathBuf) {
+        self.seen += 1;
+        match self.sample {
+            Sample::Count(n) if self.kept.len() < n => self.kept.push(path),
+            Sample::Count(n) => {
+                let j = self.rng.below(self.seen);
+                if j < n {
+                    self.kept[j] = path;
+                }
+            }
+            Sample::Rate(rate) => {
+                if self.rng.unit() < rate {
+                    self.kept.push(path);
+                }
+            }
+        }
+    }
+    /// the sample of both walks together: each kept path is drawn from one side or the
+    /// other in proportion to how many matches that side still stands for.
+    pub(crate) fn merge(mut self, mut other: Self) -> Self {
+        if let Sample::Count(n) = self.sample {
+            let (mut left, mut right) = (self.seen, other.seen);
+            let mut mine = std::mem::take(&mut self.kept);
+            let mut kept = Vec::with_capacity(n.min(left + right));
+            while kept.len() < n && left + right > 0 {
+                let side = if self.rng.below(left + right) < left {
+                    left -= 1;
+                    &mut mine
+                } else {
+                    right -= 1;
+                    &mut other.kept
+                };
+                let i = self.rng.below(side.len());
+                kept.push(side.swap_remove(i));
+            }
+            self.kept = kept;
+        } else {
+            self.kept.append(&mut other.kept);
+        }
+        self.seen += other.seen;
+        self
+    }
+    #[inline]
+    pub(crate) fn into_paths(self) -> Vec<PathBuf> {
+        self.kept
+    }
+}
+
+/// the sample of an already collected list.
+pub(crate) fn sample(paths: Vec<PathBuf>, sample: Sample) -> Vec<PathBuf> {
+    let mut sampler = Sampler::new(sample);
+    paths.into_iter().for_each(|path| sampler.push(path));
+    sampler.into_paths()
+}
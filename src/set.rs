@@ -0,0 +1,101 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    collections::{btree_set, BTreeSet},
+    iter::FromIterator,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+/// Ordered set of collected paths supporting set operations between collectors.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PathSet(BTreeSet<PathBuf>);
+
+impl PathSet {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// resolves every path first so different spellings of one file compare equal.
+    /// paths that cannot be resolved are kept as they are.
+    pub fn canonicalized(paths: Vec<PathBuf>) -> Self {
+        let paths: Vec<PathBuf> = paths
+            .into_par_iter()
+            .map(|p| p.canonicalize().unwrap_or(p))
+            .collect();
+        paths.into_iter().collect()
+    }
+    #[inline]
+    pub fn union(&self, other: &PathSet) -> PathSet {
+        PathSet(self.0.union(&other.0).cloned().collect())
+    }
+    #[inline]
+    pub fn intersection(&self, other: &PathSet) -> PathSet {
+        PathSet(self.0.intersection(&other.0).cloned().collect())
+    }
+    #[inline]
+    pub fn difference(&self, other: &PathSet) -> PathSet {
+        PathSet(self.0.difference(&other.0).cloned().collect())
+    }
+    #[inline]
+    pub fn symmetric_difference(&self, other: &PathSet) -> PathSet {
+        PathSet(self.0.symmetric_difference(&other.0).cloned().collect())
+    }
+    #[inline]
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.0.contains(path.as_ref())
+    }
+    #[inline]
+    pub fn insert(&mut self, path: PathBuf) -> bool {
+        self.0.insert(path)
+    }
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    #[inline]
+    pub fn iter(&self) -> btree_set::Iter<'_, PathBuf> {
+        self.0.iter()
+    }
+    #[inline]
+    pub fn into_vec(self) -> Vec<PathBuf> {
+        self.0.into_iter().collect()
+    }
+}
+
+impl From<Vec<PathBuf>> for PathSet {
+    #[inline]
+    fn from(paths: Vec<PathBuf>) -> Self {
+        paths.into_iter().collect()
+    }
+}
+
+impl FromIterator<PathBuf> for PathSet {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = PathBuf>>(iter: I) -> Self {
+        PathSet(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for PathSet {
+    type Item = PathBuf;
+    type IntoIter = btree_set::IntoIter<PathBuf>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PathSet {
+    type Item = &'a PathBuf;
+    type IntoIter = btree_set::Iter<'a, PathBuf>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
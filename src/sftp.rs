@@ -0,0 +1,30 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+//! A remote tree over SFTP as a walk backend, so it gets the same depth rules,
+//! error handling and matching as the local walk. The session serves one request
+//! at a time, requests from several workers wait their turn on it.
+
+use std::{io, path::Path};
+
+use ssh2::Sftp;
+
+use crate::{
+    backend::{Backend, Entries},
+    WalkConfig,
+};
+
+impl Backend for Sftp {
+    fn read_dir<'a>(&'a self, dir: &Path, _: &'a WalkConfig<'_>) -> io::Result<Entries<'a>> {
+        let entries = self.readdir(dir).map_err(io::Error::from)?;
+        // the listing describes symlinks themselves, they are reported and never entered.
+        Ok(Box::new(
+            entries
+                .into_iter()
+                .map(|(path, stat)| Ok((path, stat.is_dir()))),
+        ))
+    }
+    #[inline]
+    fn is_dir(&self, path: &Path, _: &WalkConfig<'_>) -> bool {
+        self.stat(path).is_ok_and(|stat| stat.is_dir())
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{io, path::Path};
+
+use rusqlite::{params, Connection};
+
+use crate::{entry, FileEntry, HashAlgo};
+
+const SCHEMA: &str = "
+DROP TABLE IF EXISTS entries;
+CREATE TABLE entries (
+    path  TEXT    NOT NULL PRIMARY KEY,
+    size  INTEGER NOT NULL,
+    mtime INTEGER,
+    type  TEXT    NOT NULL,
+    hash  TEXT
+);
+CREATE INDEX entries_size ON entries(size);
+CREATE INDEX entries_mtime ON entries(mtime);
+CREATE INDEX entries_hash ON entries(hash);
+";
+
+#[inline]
+fn to_io(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// `entries` into the `entries` table of the database at `db`, replacing that table.
+/// mtime is in seconds since the epoch, hash the hex digest when `algo` is set.
+pub(crate) fn write(entries: Vec<FileEntry>, db: &Path, algo: Option<HashAlgo>) -> io::Result<()> {
+    let rows = entry::rows(entries, algo);
+    let mut conn = Connection::open(db).map_err(to_io)?;
+    let tx = conn.transaction().map_err(to_io)?;
+    tx.execute_batch(SCHEMA).map_err(to_io)?;
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT OR REPLACE INTO entries (path, size, mtime, type, hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .map_err(to_io)?;
+        for row in rows {
+            insert
+                .execute(params![
+                    row.path,
+                    row.size as i64,
+                    row.mtime.map(|d| d.as_secs() as i64),
+                    row.kind,
+                    row.hash,
+                ])
+                .map_err(to_io)?;
+        }
+    }
+    tx.commit().map_err(to_io)
+}
@@ -0,0 +1,128 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    io::{self, Write},
+    mem,
+    path::PathBuf,
+    sync::mpsc::Receiver,
+    thread::JoinHandle,
+};
+
+use crate::export::path_bytes;
+
+/// directory batches buffered ahead of a slow consumer.
+#[cfg_attr(
+    all(target_family = "wasm", not(target_feature = "atomics")),
+    allow(dead_code)
+)]
+pub(crate) const BATCH_BUFFER: usize = 64;
+
+/// Matched files of one directory at a time, sorted, as the walk finishes each directory.
+///
+/// Dropping it early stops the walk.
+#[derive(Debug)]
+pub struct DirBatches {
+    rx: Receiver<Vec<PathBuf>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl DirBatches {
+    #[inline]
+    pub(crate) fn new(rx: Receiver<Vec<PathBuf>>, worker: Option<JoinHandle<()>>) -> Self {
+        Self { rx, worker }
+    }
+    /// the next batch if one is waiting, without blocking.
+    #[inline]
+    pub(crate) fn ready(&mut self) -> Option<Vec<PathBuf>> {
+        self.rx.try_recv().ok()
+    }
+    /// the batches flattened into single paths.
+    #[inline]
+    pub fn paths(self) -> impl Iterator<Item = PathBuf> {
+        self.flatten()
+    }
+}
+
+impl Iterator for DirBatches {
+    type Item = Vec<PathBuf>;
+    fn next(&mut self) -> Option<Vec<PathBuf>> {
+        if let Ok(batch) = self.rx.recv() {
+            return Some(batch);
+        }
+        // the walk is over, surface its panic if it had one.
+        if let Some(worker) = self.worker.take() {
+            worker
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e));
+        }
+        None
+    }
+}
+
+/// writes every batch to stdout as it arrives. A closed pipe ends it quietly.
+pub(crate) fn print(batches: DirBatches) -> io::Result<()> {
+    let stdout = io::stdout();
+    match write_batches(batches, io::BufWriter::new(stdout.lock())) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// one path per line, flushed whenever the walk has nothing new.
+fn write_batches<W: Write>(mut batches: DirBatches, mut out: W) -> io::Result<()> {
+    loop {
+        let batch = match batches.ready() {
+            Some(batch) => batch,
+            None => {
+                out.flush()?;
+                match batches.next() {
+                    Some(batch) => batch,
+                    None => return Ok(()),
+                }
+            }
+        };
+        for path in batch {
+            out.write_all(&path_bytes(&path))?;
+            out.write_all(b"\n")?;
+        }
+    }
+}
+
+/// Consumer of `for_each_batch`, fed chunks of a fixed size.
+pub(crate) struct BatchSink<'a, F> {
+    size: usize,
+    sink: &'a F,
+}
+
+impl<'a, F: Fn(Vec<PathBuf>)> BatchSink<'a, F> {
+    #[inline]
+    pub(crate) fn new(size: usize, sink: &'a F) -> Self {
+        Self {
+            size: size.max(1),
+            sink,
+        }
+    }
+    /// adds `path` to a per-thread `batch`, handing it over once full.
+    pub(crate) fn push(&self, mut batch: Vec<PathBuf>, path: PathBuf) -> Vec<PathBuf> {
+        batch.push(path);
+        if batch.len() == self.size {
+            (self.sink)(mem::take(&mut batch));
+        }
+        batch
+    }
+    /// joins two partial batches, handing over every full one.
+    pub(crate) fn merge(&self, mut a: Vec<PathBuf>, mut b: Vec<PathBuf>) -> Vec<PathBuf> {
+        a.append(&mut b);
+        while a.len() >= self.size {
+            let rest = a.split_off(self.size);
+            (self.sink)(mem::replace(&mut a, rest));
+        }
+        a
+    }
+    /// hands over the last, shorter batch.
+    pub(crate) fn finish(&self, rest: Vec<PathBuf>) {
+        if !rest.is_empty() {
+            (self.sink)(rest);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Parent,
+    Name,
+    Stem,
+    Ext,
+    Depth,
+    Mtime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Token(Token),
+}
+
+/// A parsed `with_template` pattern.
+#[derive(Debug, Clone)]
+pub(crate) struct Template {
+    parts: Vec<Part>,
+    /// `{depth}` counts from here, relative results are joined to it.
+    root: PathBuf,
+}
+
+impl Template {
+    /// panics on an unknown or unclosed token.
+    pub(crate) fn parse(template: &str, root: PathBuf) -> Self {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                parts.push(Part::Text(rest[..open].to_owned()));
+            }
+            let close = rest[open..]
+                .find('}')
+                .unwrap_or_else(|| panic!("* template: unclosed token in {}", template));
+            let token = match &rest[open + 1..open + close] {
+                "parent" => Token::Parent,
+                "name" => Token::Name,
+                "stem" => Token::Stem,
+                "ext" => Token::Ext,
+                "depth" => Token::Depth,
+                "mtime" => Token::Mtime,
+                other => panic!("* template: unknown token {{{}}} in {}", other, template),
+            };
+            parts.push(Part::Token(token));
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_owned()));
+        }
+        Template { parts, root }
+    }
+
+    pub(crate) fn apply(&self, path: &Path) -> io::Result<PathBuf> {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Token(Token::Parent) => {
+                    out.push_str(&path.parent().unwrap_or(Path::new("")).to_string_lossy())
+                }
+                Part::Token(Token::Name) => out.push_str(&lossy(path.file_name())),
+                Part::Token(Token::Stem) => out.push_str(&lossy(path.file_stem())),
+                Part::Token(Token::Ext) => out.push_str(&lossy(path.extension())),
+                Part::Token(Token::Depth) => {
+                    let depth = path
+                        .strip_prefix(&self.root)
+                        .map_or(0, |rel| rel.components().count());
+                    out.push_str(&depth.to_string());
+                }
+                Part::Token(Token::Mtime) => {
+                    let secs = fs::metadata(path)?
+                        .modified()?
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs());
+                    out.push_str(&date(secs));
+                }
+            }
+        }
+        Ok(self.root.join(out))
+    }
+}
+
+#[inline]
+fn lossy(part: Option<&OsStr>) -> Cow<'_, str> {
+    part.unwrap_or_default().to_string_lossy()
+}
+
+/// `YYYY-MM-DD_HHMMSS` in UTC of seconds since the epoch, safe in file names.
+pub(crate) fn timestamp(secs: u64) -> String {
+    let time = secs % 86_400;
+    format!(
+        "{}_{:02}{:02}{:02}",
+        date(secs),
+        time / 3_600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// `YYYY-MM-DD` in UTC of seconds since the epoch.
+fn date(secs: u64) -> String {
+    // days to the proleptic Gregorian calendar, after Howard Hinnant's `civil_from_days`.
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,58 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::{
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    error::{CollectError, Operation},
+    read_one, WalkConfig,
+};
+
+/// Push-based consumer for `collect_with`, called from several threads at once.
+pub trait FileVisitor: Sync {
+    /// A matched file.
+    fn file(&self, path: &Path, meta: &Metadata);
+    /// Before a directory is read, `false` leaves it out.
+    fn enter_dir(&self, _dir: &Path) -> bool {
+        true
+    }
+    /// After a directory and everything below it were visited.
+    fn leave_dir(&self, _dir: &Path) {}
+    /// A failure the walk did not stop for.
+    fn error(&self, _e: CollectError) {}
+}
+
+/// hands over what `cfg.errors` gathered, whichever thread it came from.
+fn report<V: FileVisitor>(cfg: &WalkConfig, visitor: &V) {
+    if let Some(errors) = &cfg.errors {
+        let errors = std::mem::take(&mut *errors.lock().unwrap());
+        errors.into_iter().for_each(|e| visitor.error(e));
+    }
+}
+
+pub(crate) fn visit<V: FileVisitor>(
+    dir: PathBuf,
+    depth: Option<usize>,
+    cfg: &WalkConfig,
+    visitor: &V,
+) {
+    if !visitor.enter_dir(&dir) {
+        return;
+    }
+    // entries inside archives have no metadata of their own.
+    let ((files, _entries), dirs) = read_one(dir.clone(), depth, cfg);
+    report(cfg, visitor);
+    files
+        .into_par_iter()
+        .for_each(|path| match cfg.retry(|| fs::metadata(&path)) {
+            Ok(meta) => visitor.file(&path, &meta),
+            Err(e) => visitor.error(CollectError::new(path, Operation::Metadata, e)),
+        });
+    dirs.into_par_iter()
+        .for_each(|(dir, depth)| visit(dir, depth, cfg, visitor));
+    visitor.leave_dir(&dir);
+}
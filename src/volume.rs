@@ -0,0 +1,133 @@
+// Copyright 2021 Hwakyeom Kim(=just-do-halee)
+
+use std::path::PathBuf;
+
+/// The mounted volumes of this machine: the drive letters on Windows,
+/// `/` and `/Volumes/*` on macOS, the mount points of real filesystems on Linux.
+pub fn volumes() -> Vec<PathBuf> {
+    let mut volumes: Vec<PathBuf> = list()
+        .into_iter()
+        .filter_map(|(path, pseudo)| (!pseudo).then_some(path))
+        .collect();
+    volumes.sort();
+    volumes.dedup();
+    volumes
+}
+
+/// mount points of kernel pseudo filesystems like `/proc`, left out by `collect_volumes`.
+pub(crate) fn pseudo_mounts() -> Vec<PathBuf> {
+    list()
+        .into_iter()
+        .filter_map(|(path, pseudo)| pseudo.then_some(path))
+        .collect()
+}
+
+/// the volumes not inside another one, each walked once by `collect_volumes`.
+pub(crate) fn outermost(mut volumes: Vec<PathBuf>) -> Vec<PathBuf> {
+    volumes.sort();
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for volume in volumes {
+        if !roots.iter().any(|r| volume.starts_with(r)) {
+            roots.push(volume);
+        }
+    }
+    roots
+}
+
+#[cfg(windows)]
+fn list() -> Vec<(PathBuf, bool)> {
+    (b'A'..=b'Z')
+        .map(|letter| PathBuf::from(format!("{}:\\", letter as char)))
+        .filter(|drive| drive.is_dir())
+        .map(|drive| (drive, false))
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn list() -> Vec<(PathBuf, bool)> {
+    let mounted = std::fs::read_dir("/Volumes")
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        // the boot volume shows up again as a symlink to `/`.
+        .filter(|p| std::fs::symlink_metadata(p).is_ok_and(|m| m.is_dir()));
+    std::iter::once(PathBuf::from("/"))
+        .chain(mounted)
+        .map(|volume| (volume, false))
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn list() -> Vec<(PathBuf, bool)> {
+    /// kernel interfaces, nothing to find in there.
+    const PSEUDO_FS: &[&str] = &[
+        "proc",
+        "sysfs",
+        "devtmpfs",
+        "devpts",
+        "cgroup",
+        "cgroup2",
+        "securityfs",
+        "pstore",
+        "bpf",
+        "tracefs",
+        "debugfs",
+        "configfs",
+        "fusectl",
+        "mqueue",
+        "hugetlbfs",
+        "autofs",
+        "binfmt_misc",
+        "rpc_pipefs",
+        "nsfs",
+        "efivarfs",
+    ];
+    // mountinfo: `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw`
+    let mountinfo = std::fs::read_to_string("/proc/self/mountinfo").unwrap_or_default();
+    let mut volumes: Vec<(PathBuf, bool)> = mountinfo
+        .lines()
+        .filter_map(|line| {
+            let (mount, fs) = line.split_once(" - ")?;
+            let fs_type = fs.split(' ').next()?;
+            let path = unescape(mount.split(' ').nth(4)?);
+            Some((path, PSEUDO_FS.contains(&fs_type)))
+        })
+        .collect();
+    if volumes.iter().all(|(_, pseudo)| *pseudo) {
+        volumes.push((PathBuf::from("/"), false));
+    }
+    volumes
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn list() -> Vec<(PathBuf, bool)> {
+    vec![(PathBuf::from("/"), false)]
+}
+
+/// mountinfo writes space, tab, newline and backslash as `\ooo` octal.
+#[cfg(target_os = "linux")]
+fn unescape(field: &str) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).and_then(|o| {
+            let o = std::str::from_utf8(o).ok()?;
+            u8::from_str_radix(o, 8).ok()
+        });
+        match (bytes[i], octal) {
+            (b'\\', Some(b)) => {
+                out.push(b);
+                i += 4;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(OsString::from_vec(out))
+}
@@ -0,0 +1,370 @@
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use collectfiles::*;
+use common::{names, tree};
+
+const FILES: &[&str] = &["a.txt", "sub/b.txt", "sub/deep/c.txt"];
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+}
+
+/// sources of `done`, relative to `root`.
+fn done(root: &Path, report: &ActionReport) -> Vec<String> {
+    names(
+        root,
+        report.done.iter().map(|(src, _)| src.clone()).collect(),
+    )
+}
+
+/// (path relative to `root`, operation) of every failure.
+fn failed(root: &Path, errors: &[CollectError]) -> Vec<(String, Operation)> {
+    let mut failed: Vec<_> = errors
+        .iter()
+        .map(|e| (names(root, vec![e.path.clone()]).remove(0), e.op))
+        .collect();
+    failed.sort_by(|a, b| a.0.cmp(&b.0));
+    failed
+}
+
+fn to_md(path: PathBuf) -> PathBuf {
+    path.with_extension("md")
+}
+
+#[test]
+fn copy_to_follows_collision() {
+    let root = tree("actions-copy", FILES);
+    let dest = tree("actions-copy-dest", &["a.txt", "sub/deep/c.txt/"]);
+    fs::write(dest.join("a.txt"), "old").unwrap();
+
+    let report = CollectFiles(&root).copy_to(&dest, Collision::Skip);
+    assert_eq!(done(&root, &report), ["sub/b.txt"]);
+    assert_eq!(names(&root, report.skipped), ["a.txt", "sub/deep/c.txt"]);
+    assert_eq!(read(&dest.join("a.txt")), "old");
+    assert_eq!(read(&dest.join("sub/b.txt")), "sub/b.txt");
+
+    let report = CollectFiles(&root).copy_to(&dest, Collision::Rename);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(read(&dest.join("a (1).txt")), "a.txt");
+    assert_eq!(read(&dest.join("sub/deep/c (1).txt")), "sub/deep/c.txt");
+    assert_eq!(read(&dest.join("a.txt")), "old");
+
+    // a directory in the way cannot be overwritten by a file.
+    let report = CollectFiles(&root).copy_to(&dest, Collision::Overwrite);
+    assert_eq!(done(&root, &report), ["a.txt", "sub/b.txt"]);
+    assert_eq!(
+        failed(&root, &report.failed),
+        [("sub/deep/c.txt".to_string(), Operation::Copy)]
+    );
+    assert_eq!(read(&dest.join("a.txt")), "a.txt");
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&dest).unwrap();
+}
+
+#[test]
+fn move_to_dry_run_and_failures() {
+    let root = tree("actions-move", FILES);
+    let dest = tree("actions-move-dest", &["sub"]);
+
+    let report = CollectFiles(&root).move_to(&dest, Collision::Skip, Mode::DryRun);
+    assert_eq!(
+        done(&root, &report),
+        ["a.txt", "sub/b.txt", "sub/deep/c.txt"]
+    );
+    assert_eq!(names(&root, CollectFiles(&root).collect()).len(), 3);
+    assert!(!dest.join("a.txt").exists());
+
+    // `sub` is a file on the other side, nothing can be moved under it.
+    let report = CollectFiles(&root).move_to(&dest, Collision::Skip, Mode::Execute);
+    assert_eq!(done(&root, &report), ["a.txt"]);
+    assert_eq!(
+        failed(&root, &report.failed),
+        [
+            ("sub/b.txt".to_string(), Operation::Move),
+            ("sub/deep/c.txt".to_string(), Operation::Move)
+        ]
+    );
+    assert_eq!(read(&dest.join("a.txt")), "a.txt");
+    assert!(!root.join("a.txt").exists());
+    assert!(root.join("sub/b.txt").exists());
+
+    fs::write(root.join("a.txt"), "again").unwrap();
+    let report = CollectFiles(&root).with_target_regex("a.txt$").move_to(
+        &dest,
+        Collision::Skip,
+        Mode::Execute,
+    );
+    assert_eq!(names(&root, report.skipped), ["a.txt"]);
+    assert!(root.join("a.txt").exists());
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&dest).unwrap();
+}
+
+#[test]
+fn delete_dry_run_prune_and_backup() {
+    let root = tree("actions-delete", FILES);
+    let backups = tree("actions-delete-backup", &[]);
+
+    let report = CollectFiles(&root)
+        .with_target_regex("c.txt$")
+        .delete(Mode::DryRun, true);
+    assert_eq!(names(&root, report.deleted), ["sub/deep/c.txt"]);
+    assert_eq!(names(&root, report.pruned_dirs), ["sub/deep"]);
+    assert!(root.join("sub/deep/c.txt").exists());
+
+    let report = CollectFiles(&root)
+        .with_target_regex("c.txt$")
+        .with_backup_dir(&backups)
+        .delete(Mode::Execute, true);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(names(&root, report.pruned_dirs), ["sub/deep"]);
+    assert!(!root.join("sub/deep").exists());
+    assert!(root.join("sub/b.txt").exists());
+    let saved = fs::read_dir(&backups).unwrap().next().unwrap().unwrap();
+    assert_eq!(read(&saved.path().join("sub/deep/c.txt")), "sub/deep/c.txt");
+
+    // a backup that cannot be written keeps the file.
+    let blocked = backups.join("blocked");
+    fs::write(&blocked, "").unwrap();
+    let report = CollectFiles(&root)
+        .with_target_regex("b.txt$")
+        .with_backup_dir(&blocked)
+        .delete(Mode::Execute, false);
+    assert!(report.deleted.is_empty());
+    assert_eq!(
+        failed(&root, &report.failed),
+        [("sub/b.txt".to_string(), Operation::Backup)]
+    );
+    assert!(root.join("sub/b.txt").exists());
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&backups).unwrap();
+}
+
+#[test]
+fn apply_rename_reports_conflicts() {
+    let root = tree(
+        "actions-rename",
+        &["a.txt", "a.md", "sub/b.txt", "sub/c.md"],
+    );
+
+    let report = CollectFiles(&root)
+        .with_hook(to_md)
+        .apply_rename(Mode::DryRun);
+    assert_eq!(done(&root, &report), ["sub/b.txt"]);
+    assert_eq!(names(&root, report.skipped), ["a.md", "sub/c.md"]);
+    assert_eq!(
+        failed(&root, &report.failed),
+        [("a.txt".to_string(), Operation::Rename)]
+    );
+    assert!(!root.join("sub/b.md").exists());
+
+    let report = CollectFiles(&root)
+        .with_hook(to_md)
+        .apply_rename(Mode::Execute);
+    assert_eq!(done(&root, &report), ["sub/b.txt"]);
+    assert_eq!(read(&root.join("sub/b.md")), "sub/b.txt");
+    assert_eq!(read(&root.join("a.txt")), "a.txt");
+    assert_eq!(read(&root.join("a.md")), "a.md");
+
+    // several files renamed to the same name all stay.
+    let report = CollectFiles(&root)
+        .with_target_regex("sub/.*md$")
+        .with_hook(|p| p.with_file_name("same.md"))
+        .apply_rename(Mode::Execute);
+    assert!(report.done.is_empty());
+    assert_eq!(report.failed.len(), 2);
+    assert!(!root.join("sub/same.md").exists());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn link_into_both_kinds() {
+    let root = tree("actions-link", FILES);
+    let dest = tree("actions-link-dest", &[]);
+
+    let report = CollectFiles(&root).link_into(dest.join("soft"), LinkKind::Symlink);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert!(fs::symlink_metadata(dest.join("soft/sub/b.txt"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(read(&dest.join("soft/sub/b.txt")), "sub/b.txt");
+
+    let report = CollectFiles(&root).link_into(dest.join("hard"), LinkKind::Hardlink);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(read(&dest.join("hard/sub/deep/c.txt")), "sub/deep/c.txt");
+
+    // links already in place are never replaced.
+    let report = CollectFiles(&root).link_into(dest.join("hard"), LinkKind::Hardlink);
+    assert!(report.done.is_empty());
+    assert_eq!(report.failed.len(), 3);
+    assert!(report.failed.iter().all(|e| e.op == Operation::Link));
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&dest).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn set_permissions_and_owner() {
+    use std::os::unix::fs::{symlink, MetadataExt, PermissionsExt};
+
+    let root = tree("actions-chmod", FILES);
+    symlink(root.join("gone"), root.join("broken")).unwrap();
+    let mode = |p: &str| fs::metadata(root.join(p)).unwrap().permissions().mode() & 0o777;
+    let before = mode("a.txt");
+
+    let c = CollectFiles(&root).with_target_regex(".txt$");
+    let report = c.set_permissions(0o600, Mode::DryRun);
+    assert_eq!(report.done.len(), 3);
+    assert_eq!(mode("a.txt"), before);
+
+    let report = c.set_permissions(0o600, Mode::Execute);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(mode("sub/deep/c.txt"), 0o600);
+
+    // a symlink to nowhere has nothing to change.
+    let report = CollectFiles(&root).set_permissions(0o644, Mode::Execute);
+    assert_eq!(
+        failed(&root, &report.failed),
+        [("broken".to_string(), Operation::Permissions)]
+    );
+
+    let meta = fs::metadata(root.join("a.txt")).unwrap();
+    let report = CollectFiles(&root).set_owner(Some(meta.uid()), Some(meta.gid()), Mode::Execute);
+    assert_eq!(
+        done(&root, &report),
+        ["a.txt", "sub/b.txt", "sub/deep/c.txt"]
+    );
+    assert_eq!(
+        failed(&root, &report.failed),
+        [("broken".to_string(), Operation::Owner)]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn touch_sets_times() {
+    let root = tree("actions-touch", FILES);
+    let time = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    let modified = |p: &str| fs::metadata(root.join(p)).unwrap().modified().unwrap();
+
+    let report = CollectFiles(&root).touch(time, Mode::DryRun);
+    assert_eq!(report.done.len(), 3);
+    assert_ne!(modified("a.txt"), time);
+
+    let report = CollectFiles(&root).touch(time, Mode::Execute);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(modified("a.txt"), time);
+    assert_eq!(modified("sub/deep/c.txt"), time);
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(root.join("gone"), root.join("broken")).unwrap();
+        let report = CollectFiles(&root).touch(std::time::SystemTime::now(), Mode::Execute);
+        assert_eq!(
+            failed(&root, &report.failed),
+            [("broken".to_string(), Operation::Times)]
+        );
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn transform_contents_skips_unchanged() {
+    let root = tree("actions-transform", FILES);
+    let backups = tree("actions-transform-backup", &[]);
+    let upper_a = |before: &[u8]| {
+        Ok(before
+            .iter()
+            .map(|&c| if c == b'a' { b'A' } else { c })
+            .collect())
+    };
+
+    let report = CollectFiles(&root).transform_contents(upper_a, Mode::DryRun);
+    assert_eq!(done(&root, &report), ["a.txt"]);
+    assert_eq!(
+        names(&root, report.skipped),
+        ["sub/b.txt", "sub/deep/c.txt"]
+    );
+    assert_eq!(read(&root.join("a.txt")), "a.txt");
+
+    let report = CollectFiles(&root)
+        .with_backup_dir(&backups)
+        .transform_contents(upper_a, Mode::Execute);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(read(&root.join("a.txt")), "A.txt");
+    let saved = fs::read_dir(&backups).unwrap().next().unwrap().unwrap();
+    assert_eq!(read(&saved.path().join("a.txt")), "a.txt");
+    assert!(!saved.path().join("sub/b.txt").exists());
+
+    let report = CollectFiles(&root).transform_contents(
+        |_: &[u8]| Err(std::io::Error::other("* refused")),
+        Mode::Execute,
+    );
+    assert!(report.done.is_empty());
+    assert_eq!(report.failed.len(), 3);
+    assert!(report.failed.iter().all(|e| e.op == Operation::Content));
+    assert_eq!(read(&root.join("sub/b.txt")), "sub/b.txt");
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&backups).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn remove_broken_symlinks_only() {
+    use std::os::unix::fs::symlink;
+
+    let root = tree("actions-broken", FILES);
+    symlink(root.join("a.txt"), root.join("alive")).unwrap();
+    symlink(root.join("gone"), root.join("sub/broken")).unwrap();
+
+    let report = CollectFiles(&root).remove_broken_symlinks(Mode::DryRun);
+    assert_eq!(names(&root, report.deleted), ["sub/broken"]);
+    assert!(fs::symlink_metadata(root.join("sub/broken")).is_ok());
+
+    let report = CollectFiles(&root).remove_broken_symlinks(Mode::Execute);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(names(&root, report.deleted), ["sub/broken"]);
+    assert!(fs::symlink_metadata(root.join("sub/broken")).is_err());
+    assert!(fs::symlink_metadata(root.join("alive")).is_ok());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn remove_empty_dirs_deepest_first() {
+    let root = tree(
+        "actions-empty",
+        &["a.txt", "e/", "n/e1/e2/", "n/f.txt", "keep/", "x/y/"],
+    );
+
+    let report = CollectFiles(&root)
+        .with_prune_dirs(&["keep"])
+        .remove_empty_dirs(Mode::DryRun);
+    assert_eq!(
+        names(&root, report.pruned_dirs),
+        ["e", "n/e1", "n/e1/e2", "x", "x/y"]
+    );
+    assert!(root.join("n/e1/e2").exists());
+
+    let report = CollectFiles(&root)
+        .with_prune_dirs(&["keep"])
+        .remove_empty_dirs(Mode::Execute);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.pruned_dirs.len(), 5);
+    assert!(!root.join("x").exists() && !root.join("n/e1").exists());
+    assert!(root.join("keep").exists() && root.join("n/f.txt").exists());
+
+    // the root itself is never removed.
+    let empty = tree("actions-empty-root", &[]);
+    let report = CollectFiles(&empty).remove_empty_dirs(Mode::Execute);
+    assert!(report.pruned_dirs.is_empty());
+    assert!(empty.exists());
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&empty).unwrap();
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "archive")]
+
+mod common;
+
+use std::{fs, path::Path};
+
+use collectfiles::*;
+use common::{names, tree};
+
+/// a tar holding `names` as given, without the checks of `tar::Builder::append_path`.
+fn write_raw_tar(path: &Path, names: &[&str]) {
+    let mut builder = tar::Builder::new(fs::File::create(path).unwrap());
+    for name in names.iter() {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(name.len() as u64);
+        header.set_mode(0o644);
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_cksum();
+        builder.append(&header, name.as_bytes()).unwrap();
+    }
+    builder.finish().unwrap();
+}
+
+#[test]
+fn entries_never_leave_the_archive() {
+    let root = tree("archive-escape", &["plain.txt"]);
+    let victim = std::env::temp_dir().join(format!(
+        "collectfiles-{}-archive-victim.txt",
+        std::process::id()
+    ));
+    fs::write(&victim, "victim").unwrap();
+    let absolute = victim.to_str().unwrap();
+    write_raw_tar(
+        &root.join("evil.tar"),
+        &[
+            absolute,
+            "../outside.txt",
+            "ok/../../up.txt",
+            "./ok/inside.txt",
+        ],
+    );
+
+    let c = CollectFiles(&root).with_descend_archives(true);
+    assert_eq!(
+        names(&root, c.collect()),
+        ["evil.tar", "evil.tar!/ok/inside.txt", "plain.txt"]
+    );
+    assert_eq!(
+        names(&root, c.clone().with_strategy(Traversal::Bfs).collect()),
+        ["evil.tar", "evil.tar!/ok/inside.txt", "plain.txt"]
+    );
+
+    // entries are never handed to actions, whatever their name looks like.
+    let report = c.delete(Mode::DryRun, false);
+    assert_eq!(names(&root, report.deleted), ["evil.tar", "plain.txt"]);
+    assert!(victim.exists());
+    fs::remove_file(&victim).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn entry_names_are_not_mistaken_for_files() {
+    // a real directory named like an entry stays a directory of real files.
+    let root = tree("archive-lookalike", &["a.zip!/real.txt"]);
+    write_raw_tar(&root.join("b.tar"), &["x.txt"]);
+
+    let c = CollectFiles(&root).with_descend_archives(true);
+    assert_eq!(
+        names(&root, c.collect()),
+        ["a.zip!/real.txt", "b.tar", "b.tar!/x.txt"]
+    );
+    let report = c.delete(Mode::DryRun, false);
+    assert_eq!(names(&root, report.deleted), ["a.zip!/real.txt", "b.tar"]);
+    fs::remove_dir_all(&root).unwrap();
+}
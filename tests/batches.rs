@@ -0,0 +1,31 @@
+mod common;
+
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use collectfiles::*;
+use common::{names, tree};
+
+fn upper(p: PathBuf) -> PathBuf {
+    p.with_extension("TXT")
+}
+
+#[test]
+fn for_each_batch_delivers_fixed_size_chunks() {
+    let files: Vec<String> = (0..10).map(|i| format!("d{}/f{}.txt", i % 3, i)).collect();
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    let root = tree("batches", &files);
+    let c = CollectFiles(&root).with_hook(upper);
+
+    let chunks = Mutex::new(Vec::new());
+    c.for_each_batch(4, |chunk| chunks.lock().unwrap().push(chunk));
+    let mut chunks = chunks.into_inner().unwrap();
+    chunks.sort_by_key(|chunk| std::cmp::Reverse(chunk.len()));
+    let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [4, 4, 2]);
+    assert_eq!(names(&root, chunks.concat()), names(&root, c.collect()));
+    assert!(chunks
+        .concat()
+        .iter()
+        .all(|p| p.extension().unwrap() == "TXT"));
+    fs::remove_dir_all(&root).unwrap();
+}
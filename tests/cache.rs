@@ -0,0 +1,95 @@
+#![cfg(feature = "cache")]
+
+mod common;
+
+use std::fs;
+
+use collectfiles::*;
+use common::{names, tree};
+
+#[test]
+fn caches_are_read_back_until_removed() {
+    let root = tree("cache-hit", &["a.txt", "x/b.txt"]);
+    let cache = root.with_extension("cache");
+    let c = CollectFiles(&root);
+
+    let first = c.collect_cached(&cache).unwrap();
+    assert_eq!(names(&root, first.clone()), ["a.txt", "x/b.txt"]);
+    assert!(fs::read(&cache).unwrap().starts_with(b"CFCACHE\0"));
+
+    // the tree changed, the cache still answers.
+    fs::write(root.join("c.txt"), "c").unwrap();
+    assert_eq!(c.collect_cached(&cache).unwrap(), first);
+
+    fs::remove_file(&cache).unwrap();
+    assert_eq!(names(&root, c.collect_cached(&cache).unwrap()).len(), 3);
+    fs::remove_file(&cache).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn caches_of_other_configurations_are_rebuilt() {
+    let root = tree("cache-config", &["a.txt", "b.md"]);
+    let cache = root.with_extension("cache");
+
+    CollectFiles(&root).collect_cached(&cache).unwrap();
+    let md = CollectFiles(&root).with_target_regex(r"\.md$");
+    assert_eq!(names(&root, md.collect_cached(&cache).unwrap()), ["b.md"]);
+    // rewritten for the new configuration.
+    fs::write(root.join("c.md"), "c").unwrap();
+    assert_eq!(names(&root, md.collect_cached(&cache).unwrap()), ["b.md"]);
+    fs::remove_file(&cache).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn damaged_caches_are_rebuilt() {
+    let root = tree("cache-damaged", &["a.txt"]);
+    let cache = root.with_extension("cache");
+    let c = CollectFiles(&root);
+    c.collect_cached(&cache).unwrap();
+    let good = fs::read(&cache).unwrap();
+    fs::write(root.join("b.txt"), "b").unwrap();
+
+    let mut version = good.clone();
+    version[8] ^= 0xff;
+    let mut body = good.clone();
+    *body.last_mut().unwrap() ^= 0xff;
+    for damaged in [
+        version,
+        body,
+        good[..good.len() - 1].to_vec(),
+        good[..20].to_vec(),
+        b"not a cache".to_vec(),
+    ] {
+        fs::write(&cache, damaged).unwrap();
+        assert_eq!(
+            names(&root, c.collect_cached(&cache).unwrap()),
+            ["a.txt", "b.txt"]
+        );
+    }
+    fs::remove_file(&cache).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn caches_keep_names_byte_for_byte() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let root = tree("cache-bytes", &["back\\slash", "new\nline"]);
+    fs::write(root.join(OsStr::from_bytes(b"caf\xe9")), "c").unwrap();
+    let cache = root.with_extension("cache");
+    let c = CollectFiles(&root);
+
+    let mut written = c.collect_cached(&cache).unwrap();
+    // left out of the cache, so only a rebuild would list it.
+    fs::write(root.join("later"), "").unwrap();
+    let mut read = c.collect_cached(&cache).unwrap();
+    written.sort();
+    read.sort();
+    assert_eq!(read, written);
+    assert!(read.contains(&root.join(OsStr::from_bytes(b"caf\xe9"))));
+    fs::remove_file(&cache).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use collectfiles::*;
+use common::{names, tree};
+
+fn drop_b(p: PathBuf) -> Option<PathBuf> {
+    if p.ends_with("b.txt") {
+        None
+    } else {
+        Some(p)
+    }
+}
+
+#[test]
+fn collect_into_reuses_the_buffer() {
+    let root = tree("collect-into", &["a.txt", "b.txt", "sub/c.txt"]);
+    let c = CollectFiles(&root).with_filter_map_hook(drop_b);
+
+    let mut buf = Vec::with_capacity(64);
+    let (ptr, capacity) = (buf.as_ptr(), buf.capacity());
+    for _ in 0..3 {
+        buf.clear();
+        c.collect_into(&mut buf);
+        assert_eq!(buf.as_ptr(), ptr);
+        assert_eq!(buf.capacity(), capacity);
+        assert_eq!(names(&root, buf.clone()), names(&root, c.collect()));
+    }
+    assert_eq!(names(&root, buf.clone()), ["a.txt", "sub/c.txt"]);
+
+    let mut plain = Vec::with_capacity(64);
+    let ptr = plain.as_ptr();
+    CollectFiles(&root).collect_into(&mut plain);
+    assert_eq!(plain.as_ptr(), ptr);
+    assert_eq!(plain.len(), 3);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn collect_into_appends_after_existing_paths() {
+    let root = tree("collect-into-append", &["a.txt", "b.txt"]);
+    let kept = PathBuf::from("b.txt");
+    let mut buf = vec![kept.clone()];
+    CollectFiles(&root)
+        .with_filter_map_hook(drop_b)
+        .collect_into(&mut buf);
+    assert_eq!(buf, [kept, root.join("a.txt")]);
+    fs::remove_dir_all(&root).unwrap();
+}
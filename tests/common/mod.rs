@@ -0,0 +1,33 @@
+// shared by every test crate, each using only some of the helpers.
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// fresh directory under the system temp dir holding `files`, each containing its own name.
+pub fn tree(name: &str, files: &[&str]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("collectfiles-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    for f in files.iter() {
+        let path = root.join(f);
+        if f.ends_with('/') {
+            fs::create_dir_all(path).unwrap();
+        } else {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, f).unwrap();
+        }
+    }
+    root
+}
+
+/// sorted paths relative to `root`.
+pub fn names(root: &Path, mut paths: Vec<PathBuf>) -> Vec<String> {
+    paths.sort();
+    paths
+        .iter()
+        .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+        .collect()
+}
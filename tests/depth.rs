@@ -0,0 +1,71 @@
+mod common;
+
+use std::fs;
+
+use collectfiles::*;
+use common::names;
+
+const FILES: &[&str] = &["f0", "a/f1", "a/b/f2", "a/b/c/f3"];
+
+fn tree(name: &str) -> std::path::PathBuf {
+    common::tree(&format!("depth-{}", name), FILES)
+}
+
+#[test]
+fn max_depth_is_inclusive() {
+    let root = tree("inclusive");
+    let expected: [&[&str]; 5] = [
+        &[],
+        &["f0"],
+        &["a/f1", "f0"],
+        &["a/b/f2", "a/f1", "f0"],
+        &["a/b/c/f3", "a/b/f2", "a/f1", "f0"],
+    ];
+    for (n, expected) in expected.iter().enumerate() {
+        let c = CollectFiles(&root).with_max_depth(n);
+        assert_eq!(names(&root, c.collect()), *expected, "max_depth {}", n);
+    }
+    assert_eq!(
+        names(&root, CollectFiles(&root).with_max_depth(100).collect()).len(),
+        4
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn max_depth_matches_with_depth() {
+    let root = tree("with-depth");
+    for n in 0..5 {
+        assert_eq!(
+            names(&root, CollectFiles(&root).with_max_depth(n + 1).collect()),
+            names(&root, CollectFiles(&root).with_depth(n).collect()),
+            "depth {}",
+            n
+        );
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn max_depth_same_for_every_traversal() {
+    let root = tree("traversal");
+    for n in 0..5 {
+        let c = CollectFiles(&root).with_max_depth(n);
+        let dfs = names(&root, c.collect());
+        let bfs = names(&root, c.clone().with_strategy(Traversal::Bfs).collect());
+        let resumed = names(&root, c.resume(c.cursor()));
+        assert_eq!(dfs, bfs, "max_depth {}", n);
+        assert_eq!(dfs, resumed, "max_depth {}", n);
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn later_depth_setting_wins() {
+    let root = tree("override");
+    let c = CollectFiles(&root).with_max_depth(0).with_depth(0);
+    assert_eq!(names(&root, c.collect()), ["f0"]);
+    let c = CollectFiles(&root).with_depth(3).with_max_depth(1);
+    assert_eq!(names(&root, c.collect()), ["f0"]);
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use collectfiles::*;
+use common::{names, tree};
+
+const FILES: &[&str] = &["f0", "a/f1", "a/b/f2", "c/f3", "c/d/f4"];
+
+static ENTERED: AtomicUsize = AtomicUsize::new(0);
+
+fn count_entered(_: &Path) -> DirAction {
+    ENTERED.fetch_add(1, Ordering::SeqCst);
+    DirAction::Descend
+}
+
+#[test]
+fn dir_hook_runs_once_per_directory() {
+    let root = tree("estimate-hooks", FILES);
+    let c = CollectFiles(&root).with_dir_hook(count_entered);
+
+    assert_eq!(c.collect().len(), 5);
+    assert_eq!(ENTERED.swap(0, Ordering::SeqCst), 4);
+
+    // the probes leave the hooks to the real walk.
+    let estimate = c.estimate();
+    assert!(estimate.files > 0.0);
+    assert_eq!(ENTERED.load(Ordering::SeqCst), 0);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn capacity_hint_only_sizes() {
+    let root = tree("estimate-capacity", FILES);
+    let c = CollectFiles(&root);
+    let expected = names(&root, c.collect());
+    for hint in [0, 1, c.estimate().capacity(), 1_000] {
+        let paths = CollectFiles(&root).with_capacity_hint(hint).collect();
+        assert_eq!(names(&root, paths), expected, "hint {}", hint);
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn estimate_of_a_flat_tree_is_exact() {
+    let root = tree("estimate-flat", &["a", "b", "c"]);
+    let estimate = CollectFiles(&root).estimate();
+    assert_eq!(estimate.capacity(), 3);
+    assert_eq!(estimate.bytes, 3.0);
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,109 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::Path, process::Command, sync::mpsc, thread, time::Duration};
+
+use collectfiles::*;
+use common::tree;
+
+/// a FIFO nobody writes to at `path`, opening it for reading would block.
+fn mkfifo(path: &Path) {
+    let status = Command::new("mkfifo").arg(path).status().unwrap();
+    assert!(status.success());
+}
+
+/// `f()`, failing instead of hanging when it takes more than a few seconds.
+fn within<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || tx.send(f()));
+    rx.recv_timeout(Duration::from_secs(10))
+        .expect("blocked on the FIFO")
+}
+
+/// paths of the files a content pass could not read.
+fn errored<T>(report: &ContentReport<T>) -> Vec<&Path> {
+    report.errors.iter().map(|e| e.path.as_path()).collect()
+}
+
+#[test]
+fn check_access_does_not_open_fifos() {
+    let root = tree("fifo-access", &["a.txt", "sub/"]);
+    mkfifo(&root.join("pipe"));
+
+    let r = root.clone();
+    let report = within(move || CollectFiles(&r).check_access(true));
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.checked, 3);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn duplicates_leave_fifos_out() {
+    let root = tree("fifo-dupes", &["a.txt", "b/"]);
+    fs::write(root.join("b/a.txt"), "a.txt").unwrap();
+    // a FIFO reports a size of 0 too.
+    fs::write(root.join("empty"), "").unwrap();
+    mkfifo(&root.join("pipe"));
+
+    for stage in [DupeStage::Size, DupeStage::Edges, DupeStage::Full] {
+        let r = root.clone();
+        let groups = within(move || CollectFiles(&r).collect_duplicates_by(stage));
+        assert_eq!(groups, [[root.join("a.txt"), root.join("b/a.txt")]]);
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn content_passes_report_fifos() {
+    let root = tree("fifo-content", &["a.txt"]);
+    let pipe = root.join("pipe");
+    mkfifo(&pipe);
+
+    let r = root.clone();
+    let (hashed, lines, encodings, contents) = within(move || {
+        let c = CollectFiles(&r);
+        (
+            c.collect_hashed(HashAlgo::Blake3),
+            c.collect_line_counts(u64::MAX),
+            c.collect_encodings(),
+            c.collect_contents(u64::MAX, u64::MAX),
+        )
+    });
+    assert_eq!(hashed.files.len(), 1);
+    assert_eq!(errored(&hashed), [pipe.as_path()]);
+    assert_eq!(lines.files, [(root.join("a.txt"), 1)]);
+    assert_eq!(errored(&lines), [pipe.as_path()]);
+    assert_eq!(encodings.files.len(), 1);
+    assert_eq!(errored(&encodings), [pipe.as_path()]);
+    assert_eq!(contents.files, [(root.join("a.txt"), b"a.txt".to_vec())]);
+    assert_eq!(errored(&contents), [pipe.as_path()]);
+
+    #[cfg(feature = "mmap")]
+    {
+        let r = root.clone();
+        let mapped = within(move || CollectFiles(&r).collect_mmaps());
+        assert_eq!(mapped.files.len(), 1);
+        assert_eq!(errored(&mapped), [pipe.as_path()]);
+    }
+
+    let r = root.clone();
+    let (text, mime) = within(move || {
+        (
+            CollectFiles(&r).with_text_only().collect(),
+            CollectFiles(&r).with_mime_filter(&["*/*"]).collect(),
+        )
+    });
+    assert_eq!(text, [root.join("a.txt")]);
+    assert!(mime.is_empty());
+
+    let r = root.clone();
+    let manifest = within(move || {
+        let mut out = Vec::new();
+        CollectFiles(&r)
+            .write_manifest(&mut out, HashAlgo::Sha256)
+            .map(|_| out)
+    });
+    assert!(manifest.unwrap_err().to_string().contains("pipe"));
+    fs::remove_dir_all(&root).unwrap();
+}
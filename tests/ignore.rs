@@ -0,0 +1,89 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use collectfiles::*;
+use common::{names, tree};
+
+/// relative paths `with_ignore_files` keeps under `root`, the ignore files left out.
+fn kept(root: &Path) -> Vec<String> {
+    let found = CollectFiles(root).with_ignore_files(true).collect();
+    let mut found = names(root, found);
+    found.retain(|n| !n.ends_with(".ignore"));
+    found
+}
+
+#[test]
+fn globs_follow_gitignore_syntax() {
+    let root = tree(
+        "ignore-globs",
+        &[
+            "a.log",
+            "x/b.log",
+            "keep.log",
+            "a.tmp",
+            "ab.tmp",
+            "a.txt",
+            "b.txt",
+            "c.txt",
+            "top.txt",
+            "x/top.txt",
+            "x/c.dat",
+            "x/y/z/c.dat",
+            "y/c.dat",
+            "#hash",
+            "[oops",
+            "build/out.txt",
+            "y/build",
+        ],
+    );
+    let rules = [
+        "# a comment, then a blank line",
+        "",
+        "*.log",
+        "!keep.log",
+        "?.tmp",
+        "[ab].txt",
+        "/top.txt",
+        "x/**/c.dat",
+        "\\#hash",
+        "[oops",
+        "build/",
+    ];
+    fs::write(root.join(".ignore"), rules.join("\n")).unwrap();
+
+    assert_eq!(
+        kept(&root),
+        [
+            "ab.tmp",
+            "c.txt",
+            "keep.log",
+            "x/top.txt",
+            "y/build",
+            "y/c.dat"
+        ]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn classes_stars_and_deeper_files() {
+    let root = tree(
+        "ignore-nested",
+        &[
+            "a1.txt",
+            "b1.txt",
+            "c1.txt",
+            "x/a.md",
+            "x/y/a.md",
+            "x/deep.md",
+            "x/y/deep.md",
+        ],
+    );
+    fs::write(root.join(".ignore"), "[!a]1.txt\nx/*.md\n**/deep.md\n").unwrap();
+    // a deeper file overrides a shallower one.
+    fs::write(root.join("x/y/.ignore"), "!deep.md\n").unwrap();
+
+    assert_eq!(kept(&root), ["a1.txt", "x/y/a.md", "x/y/deep.md"]);
+    fs::remove_dir_all(&root).unwrap();
+}
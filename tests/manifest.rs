@@ -0,0 +1,118 @@
+mod common;
+
+use std::{fs, io, path::PathBuf};
+
+use collectfiles::*;
+use common::tree;
+
+/// hex sha256 of `bytes`, as a manifest line lists it.
+fn sha256(bytes: &[u8]) -> String {
+    HashAlgo::Sha256.hash_reader(bytes).unwrap().to_hex()
+}
+
+#[test]
+fn manifests_round_trip() {
+    let root = tree("manifest-plain", &["a.txt", "sub/b.txt"]);
+    let c = CollectFiles(&root);
+    let mut manifest = Vec::new();
+    c.write_manifest(&mut manifest, HashAlgo::Sha256).unwrap();
+
+    let text = String::from_utf8(manifest.clone()).unwrap();
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            format!("{}  a.txt", sha256(b"a.txt")),
+            format!("{}  sub/b.txt", sha256(b"sub/b.txt")),
+        ]
+    );
+
+    let report = c
+        .verify_manifest(manifest.as_slice(), HashAlgo::Sha256)
+        .unwrap();
+    assert!(report.is_ok(), "{:?}", report);
+    assert_eq!(
+        report.ok,
+        [PathBuf::from("a.txt"), PathBuf::from("sub/b.txt")]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn escaped_names_round_trip() {
+    let root = tree(
+        "manifest-escaped",
+        &["back\\slash.txt", "new\nline.txt", "plain.txt"],
+    );
+    let c = CollectFiles(&root);
+    let mut manifest = Vec::new();
+    c.write_manifest(&mut manifest, HashAlgo::Sha256).unwrap();
+
+    // like coreutils, only lines of names holding `\` or a newline are escaped.
+    let text = String::from_utf8(manifest.clone()).unwrap();
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            format!("\\{}  back\\\\slash.txt", sha256(b"back\\slash.txt")),
+            format!("\\{}  new\\nline.txt", sha256(b"new\nline.txt")),
+            format!("{}  plain.txt", sha256(b"plain.txt")),
+        ]
+    );
+
+    let report = c
+        .verify_manifest(manifest.as_slice(), HashAlgo::Sha256)
+        .unwrap();
+    assert!(report.is_ok(), "{:?}", report);
+    assert_eq!(report.ok.len(), 3);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn foreign_manifests_are_read() {
+    let root = tree("manifest-foreign", &["a.txt", "b.txt", "c.txt"]);
+    fs::write(root.join("a.txt"), "changed").unwrap();
+    // `sha256sum -b` style binary markers, CRLF line ends, an escaped plain name.
+    let manifest = format!(
+        "{}  a.txt\r\n{} *b.txt\r\n\\{}  c.txt\r\n\r\n{}  gone.txt\r\n",
+        sha256(b"a.txt"),
+        sha256(b"b.txt"),
+        sha256(b"c.txt"),
+        sha256(b"gone.txt"),
+    );
+    let report = CollectFiles(&root)
+        .verify_manifest(manifest.as_bytes(), HashAlgo::Sha256)
+        .unwrap();
+    assert_eq!(
+        report,
+        VerifyReport {
+            ok: vec!["b.txt".into(), "c.txt".into()],
+            missing: vec!["gone.txt".into()],
+            modified: vec!["a.txt".into()],
+            extra: vec![],
+        }
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn malformed_manifest_lines_are_errors() {
+    let root = tree("manifest-malformed", &["a.txt"]);
+    let hash = sha256(b"a.txt");
+    for line in [
+        format!("{} -a.txt", hash),
+        format!("{}  a.txt", &hash[1..]),
+        format!("\\{}  a\\x.txt", hash),
+        format!("\\{}  a.txt\\", hash),
+        "not a manifest".to_string(),
+    ] {
+        let e = CollectFiles(&root)
+            .verify_manifest(line.as_bytes(), HashAlgo::Sha256)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{}", line);
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use collectfiles::{private::CollectFilesConfigured, *};
+use common::{names, tree};
+
+const FILES: &[&str] = &[
+    "keep.md",
+    "drop.txt",
+    "empty/",
+    "a/keep.md",
+    "a/drop.txt",
+    "a/empty/",
+    "a/b/drop.txt",
+    "a/b/c/keep.md",
+    "only-dirs/x/y/",
+];
+
+/// every way of walking the same configuration.
+fn walks(c: &CollectFilesConfigured) -> Vec<Vec<PathBuf>> {
+    vec![
+        c.collect(),
+        c.clone().with_stable_order(true).collect(),
+        c.clone().with_strategy(Traversal::Bfs).collect(),
+        c.resume(c.cursor()),
+        c.try_collect().paths,
+    ]
+}
+
+#[test]
+fn never_reports_empty_paths() {
+    let root = tree("matching-empty", FILES);
+    let configs = [
+        CollectFiles(&root).with_target_regex(".md$"),
+        CollectFiles(&root).with_target_regex("^$"),
+        CollectFiles(&root).with_target_regex("nothing-matches-this"),
+        CollectFiles(&root).with_depth(0),
+        CollectFiles(&root).with_depth(1),
+        CollectFiles(&root).with_max_depth(0),
+        CollectFiles(&root.join("only-dirs")).with_depth(usize::MAX),
+        CollectFiles(&root.join("empty")).with_depth(usize::MAX),
+    ];
+    for c in configs.iter() {
+        for paths in walks(c) {
+            assert!(
+                paths
+                    .iter()
+                    .all(|p| !p.as_os_str().is_empty() && p.is_file()),
+                "{:?}",
+                paths
+            );
+        }
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn non_matching_entries_leave_no_trace() {
+    let root = tree("matching-regex", FILES);
+    let c = CollectFiles(&root).with_target_regex(".md$");
+    for paths in walks(&c) {
+        assert_eq!(
+            names(&root, paths),
+            ["a/b/c/keep.md", "a/keep.md", "keep.md"]
+        );
+    }
+    let c = CollectFiles(&root).with_target_regex(".md$").with_depth(1);
+    for paths in walks(&c) {
+        assert_eq!(names(&root, paths), ["a/keep.md", "keep.md"]);
+    }
+    let c = CollectFiles(&root).with_target_regex("nothing-matches-this");
+    for paths in walks(&c) {
+        assert!(paths.is_empty(), "{:?}", paths);
+    }
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,28 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use collectfiles::*;
+use common::tree;
+
+#[test]
+fn openat_walks_past_path_max() {
+    let root = tree("openat-deep", &["top.txt"]);
+    // two chains under `PATH_MAX` each, one moved to the bottom of the other.
+    let chain: PathBuf = std::iter::repeat_n("d".repeat(200), 15).collect();
+    fs::create_dir_all(root.join("a").join(&chain)).unwrap();
+    fs::create_dir_all(root.join("b").join(&chain)).unwrap();
+    fs::write(root.join("b").join(&chain).join("leaf.txt"), "leaf").unwrap();
+    fs::rename(root.join("b"), root.join("a").join(&chain).join("b")).unwrap();
+
+    let found = CollectFiles(&root)
+        .with_openat(true)
+        .with_max_open_dirs(2)
+        .collect();
+    assert_eq!(found.len(), 2);
+    let leaf = found.iter().find(|p| p.ends_with("leaf.txt")).unwrap();
+    assert!(leaf.as_os_str().len() > 2 * 15 * 200);
+    fs::remove_dir_all(&root).unwrap();
+}
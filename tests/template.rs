@@ -0,0 +1,91 @@
+mod common;
+
+use std::{
+    fs,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use collectfiles::*;
+use common::{names, tree};
+
+#[test]
+fn rename_templates_expand_captures() {
+    let root = tree("template-captures", &["x/a.log", "x/b.txt", "y/c.log"]);
+
+    let c = CollectFiles(&root).with_rename_template(r"(\w+)[/\\](\w+)\.log$", "archive/$1/$2.bak");
+    // paths the regex doesn't match pass unchanged.
+    assert_eq!(
+        names(&root, c.collect()),
+        ["archive/x/a.bak", "archive/y/c.bak", "x/b.txt"]
+    );
+
+    let c = CollectFiles(&root).with_rename_template(
+        r"(?P<dir>\w+)[/\\](?P<stem>\w+)\.txt$",
+        "${stem}_${dir}.txt",
+    );
+    assert_eq!(names(&root, c.collect()), ["b_x.txt", "x/a.log", "y/c.log"]);
+
+    let report = c.apply_rename(Mode::Execute);
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(fs::read_to_string(root.join("b_x.txt")).unwrap(), "x/b.txt");
+    assert!(!root.join("x/b.txt").exists());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn token_templates_fill_every_token() {
+    let root = tree("template-tokens", &["top.txt", "x/a.tar.gz", "x/noext"]);
+
+    let c = CollectFiles(&root).with_template("{parent}/{stem}_backup.{ext}");
+    assert_eq!(
+        names(&root, c.collect()),
+        ["top_backup.txt", "x/a.tar_backup.gz", "x/noext_backup."]
+    );
+    let c = CollectFiles(&root).with_template("d{depth}/{name}");
+    assert_eq!(
+        names(&root, c.collect()),
+        ["d1/top.txt", "d2/a.tar.gz", "d2/noext"]
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn mtime_tokens_are_utc_dates() {
+    let days = [
+        ("epoch", 0, "1970-01-01"),
+        ("leap", 951_782_400, "2000-02-29"),
+        ("eve", 946_684_799, "1999-12-31"),
+        ("century", 4_107_542_400, "2100-03-01"),
+    ];
+    let names_only: Vec<&str> = days.iter().map(|(name, _, _)| *name).collect();
+    let root = tree("template-mtime", &names_only);
+    for (name, secs, _) in days.iter() {
+        let file = fs::File::options()
+            .write(true)
+            .open(root.join(name))
+            .unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(*secs))
+            .unwrap();
+    }
+
+    let c = CollectFiles(&root).with_template("{mtime}/{name}");
+    let mut expected: Vec<String> = days
+        .iter()
+        .map(|(name, _, date)| format!("{}/{}", date, name))
+        .collect();
+    expected.sort();
+    assert_eq!(names(&root, c.collect()), expected);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "unknown token {size}")]
+fn unknown_tokens_panic() {
+    CollectFiles(".").with_template("{stem}.{size}");
+}
+
+#[test]
+#[should_panic(expected = "unclosed token")]
+fn unclosed_tokens_panic() {
+    CollectFiles(".").with_template("{parent}/{stem");
+}